@@ -0,0 +1,196 @@
+//! Convert positions between the canonical spiral and ones numbered with other conventions, i.e.
+//! starting each ring at a different tip or going around it counter-clockwise.
+
+use crate::position::{checked_ring_offset, ring, Direction, PosInt};
+
+/// The direction in which the positions grow within a ring.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Winding {
+    #[default]
+    Clockwise,
+    CounterClockwise,
+}
+
+/// The way in which the hexes of every ring are numbered: the direction of the tip each ring
+/// starts at and the direction in which the positions grow. For pointy-topped hexes the tips are
+/// rotated by 30° clockwise, so e.g. [`Direction::NE`] is the east tip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpiralConvention {
+    pub start: Direction,
+    pub winding: Winding,
+}
+
+impl SpiralConvention {
+    /// The convention used by the rest of the crate: every ring starts at the top and the
+    /// positions grow clockwise.
+    pub const CANONICAL: Self = Self::new(Direction::N, Winding::Clockwise);
+
+    /// Creates a new `SpiralConvention` from its components.
+    pub const fn new(start: Direction, winding: Winding) -> Self {
+        Self { start, winding }
+    }
+
+    // Converts the index of a hex within a ring of the given length from this convention to the
+    // canonical one; `start_idx` is the canonical index of the starting tip.
+    fn idx_to_canonical<P: PosInt>(self, idx: P, start_idx: P, len: P) -> P {
+        match self.winding {
+            Winding::Clockwise => (idx + start_idx) % len,
+            Winding::CounterClockwise => (start_idx + len - idx) % len,
+        }
+    }
+
+    // Converts the index of a hex within a ring of the given length from the canonical convention
+    // to this one; `start_idx` is the canonical index of the starting tip.
+    fn idx_from_canonical<P: PosInt>(self, idx: P, start_idx: P, len: P) -> P {
+        match self.winding {
+            Winding::Clockwise => (idx + len - start_idx) % len,
+            // Counter-clockwise numbering is its own inverse.
+            Winding::CounterClockwise => self.idx_to_canonical(idx, start_idx, len),
+        }
+    }
+}
+
+impl Default for SpiralConvention {
+    fn default() -> Self {
+        Self::CANONICAL
+    }
+}
+
+/// Converts a position numbered according to the `from` convention to the `to` one; it stays in
+/// the same ring and points at the same hex.
+///
+/// # Panics
+///
+/// Panics if the result doesn't fit in `P`, which can only happen in the outermost ring that
+/// is partially representable as a `P`.
+pub fn reindex<P: PosInt>(pos: P, from: SpiralConvention, to: SpiralConvention) -> P {
+    checked_reindex(pos, from, to).expect("reindexed position overflow")
+}
+
+/// Converts a position numbered according to the `from` convention to the `to` one, or returns
+/// `None` if the result can't be represented as a `P`.
+pub fn checked_reindex<P: PosInt>(
+    pos: P,
+    from: SpiralConvention,
+    to: SpiralConvention,
+) -> Option<P> {
+    let ring = ring(pos);
+
+    if ring.is_zero() {
+        return Some(pos);
+    }
+
+    let ring_offset = checked_ring_offset(ring)?;
+    let six: P = 6.into();
+    let len = ring.checked_mul(&six)?;
+    let start_idx = |dir: Direction| -> P { ring * (dir.index() as u8).into() };
+
+    let idx = from.idx_to_canonical(pos - ring_offset, start_idx(from.start), len);
+    let idx = to.idx_from_canonical(idx, start_idx(to.start), len);
+
+    ring_offset.checked_add(&idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        convert::{spiral_to_cube, Cube},
+        position::{neighboring_positions, ring_offset, Pos},
+    };
+
+    fn all_conventions() -> impl Iterator<Item = SpiralConvention> {
+        Direction::iter().flat_map(|start| {
+            [Winding::Clockwise, Winding::CounterClockwise]
+                .into_iter()
+                .map(move |winding| SpiralConvention::new(start, winding))
+        })
+    }
+
+    #[test]
+    fn reindex_first_ring() {
+        use Direction::*;
+        use Winding::*;
+
+        let canonical = SpiralConvention::CANONICAL;
+        let reindexed = |to| {
+            (0..7usize)
+                .map(|pos| reindex(pos, canonical, to))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(reindexed(canonical), [0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            reindexed(SpiralConvention::new(N, CounterClockwise)),
+            [0, 1, 6, 5, 4, 3, 2]
+        );
+        assert_eq!(
+            reindexed(SpiralConvention::new(NE, Clockwise)),
+            [0, 6, 1, 2, 3, 4, 5]
+        );
+        assert_eq!(
+            reindexed(SpiralConvention::new(S, CounterClockwise)),
+            [0, 4, 3, 2, 1, 6, 5]
+        );
+
+        // the starting tip of every ring is the first position
+        for conv in all_conventions() {
+            for ring in 1..10usize {
+                let tip = ring_offset(ring) + conv.start.index() * ring;
+                assert_eq!(reindex(tip, canonical, conv), ring_offset(ring));
+            }
+        }
+    }
+
+    #[test]
+    fn reindex_geometry() {
+        let canonical = SpiralConvention::CANONICAL;
+        let rotate_cw = |c: Cube| Cube::new_unchecked(-c.r(), -c.s(), -c.q());
+        let mirror = |c: Cube| Cube::new_unchecked(-c.q(), -c.s(), -c.r());
+
+        for conv in all_conventions() {
+            for pos in 0..ring_offset(10usize) {
+                let canonical_pos = reindex(pos, conv, canonical);
+
+                // the conventions are rotations and mirror images of the canonical one
+                let mut expected = spiral_to_cube(pos);
+                if conv.winding == Winding::CounterClockwise {
+                    expected = mirror(expected);
+                }
+                for _ in 0..conv.start.index() {
+                    expected = rotate_cw(expected);
+                }
+                assert_eq!(spiral_to_cube(canonical_pos), expected);
+
+                // which means that the neighbors remain neighbors
+                for neighbor in neighboring_positions(pos) {
+                    assert!(neighboring_positions(canonical_pos)
+                        .contains(&reindex(neighbor, conv, canonical)));
+                }
+
+                for other in all_conventions() {
+                    let other_pos = reindex(pos, conv, other);
+                    assert_eq!(reindex(other_pos, other, conv), pos);
+                    assert_eq!(reindex(other_pos, other, canonical), canonical_pos);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reindex_overflow() {
+        let canonical = SpiralConvention::CANONICAL;
+        let start_ne = SpiralConvention::new(Direction::NE, Winding::Clockwise);
+        let start_s = SpiralConvention::new(Direction::S, Winding::Clockwise);
+
+        // the outermost representable ring starts at 217 and ends at 270
+        assert_eq!(checked_reindex(217u8, canonical, start_s), Some(244));
+        assert_eq!(checked_reindex(217u8, canonical, start_ne), None);
+        assert_eq!(
+            checked_reindex(Pos::MAX, canonical, canonical),
+            Some(Pos::MAX)
+        );
+    }
+}
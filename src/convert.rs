@@ -1,129 +1,562 @@
-//! Convert spiral coordinates to and from cube (q, r, s) coordinates.
+//! Convert spiral coordinates to and from cube (q, r, s), axial (q, r), offset (col, row) and
+//! doubled (col, row) coordinates.
 
-use crate::position::{ring, ring_offset};
+use core::ops::{Add, Mul, Neg, Sub};
 
-/// Cube coordinate system for hex grid.
-#[derive(Debug, Default, PartialEq, Eq)]
+use crate::{
+    error::HexError,
+    position::{checked_ring_offset, ring, ring_edge_index, ring_offset, Direction, PosInt},
+};
+
+/// Cube coordinate system for hex grid; when deserialized, its components need to sum to 0.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "CubeRepr"))]
 pub struct Cube {
-    q: i32,
-    r: i32,
-    s: i32,
+    q: i64,
+    r: i64,
+    s: i64,
 }
 
 impl Cube {
-    fn new(q: i32, r: i32, s: i32) -> Self {
+    /// Creates a new `Cube` from its components, which need to sum to 0.
+    pub fn new(q: i64, r: i64, s: i64) -> Result<Self, HexError> {
+        let cube = Cube::new_unchecked(q, r, s);
+
+        if cube.component_sum() != 0 {
+            return Err(HexError::InvalidCubeSum { q, r, s });
+        }
+
+        Ok(cube)
+    }
+
+    /// Creates a new `Cube` without checking if its components sum to 0; such an invalid
+    /// coordinate is rejected when converted to a spiral position.
+    pub const fn new_unchecked(q: i64, r: i64, s: i64) -> Self {
         Cube { q, r, s }
     }
 
-    // Find the largest absolute value of cube coordinate components.
-    fn abs_largest(&self) -> i32 {
-        [self.q.abs(), self.r.abs(), self.s.abs()]
+    /// Returns the `q` component.
+    pub const fn q(&self) -> i64 {
+        self.q
+    }
+
+    /// Returns the `r` component.
+    pub const fn r(&self) -> i64 {
+        self.r
+    }
+
+    /// Returns the `s` component.
+    pub const fn s(&self) -> i64 {
+        self.s
+    }
+
+    /// Returns the distance to the other cube coordinate, i.e. the number of steps between
+    /// neighboring hexes it takes to get there.
+    pub fn distance(&self, other: &Cube) -> u64 {
+        [
+            self.q as i128 - other.q as i128,
+            self.r as i128 - other.r as i128,
+            self.s as i128 - other.s as i128,
+        ]
+        .into_iter()
+        .map(|diff| diff.unsigned_abs() as u64)
+        .max()
+        .unwrap()
+    }
+
+    // Returns the hex at `step / steps` of the way to the other cube coordinate, on the straight
+    // line between them. It's calculated exactly, with ties broken as if this coordinate was
+    // nudged by a tiny epsilon multiple of (1, 2, -3), so that the line is always continuous.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn lerp_round(&self, other: &Cube, step: u64, steps: u64) -> Cube {
+        const NUDGE: [i128; 3] = [1, 2, -3];
+
+        if steps == 0 {
+            return *self;
+        }
+
+        let (from, to) = ([self.q, self.r, self.s], [other.q, other.r, other.s]);
+        let n = steps as i128;
+
+        // The offsets from this coordinate are `diffs[c] / n`; since each component difference
+        // is at most the distance, they can't overflow for any line that could be collected.
+        let diffs: [i128; 3] =
+            core::array::from_fn(|c| (to[c] as i128 - from[c] as i128) * step as i128);
+
+        // Round to the nearest integer; exact halves are rounded in the direction of the nudge.
+        let mut rounded: [i128; 3] = core::array::from_fn(|c| {
+            let twice = 2 * diffs[c] + n;
+            let floor = twice.div_euclid(2 * n);
+
+            if twice.rem_euclid(2 * n) == 0 && NUDGE[c] < 0 {
+                floor - 1
+            } else {
+                floor
+            }
+        });
+
+        // The rounding error of each component, scaled by `n`, followed by the sign of the
+        // nudge's contribution, which decides when the errors are otherwise equal.
+        let errors: [(i128, i128); 3] = core::array::from_fn(|c| {
+            let error = rounded[c] * n - diffs[c];
+            let nudge = match error.signum() {
+                1 => -NUDGE[c],
+                -1 => NUDGE[c],
+                _ => NUDGE[c].abs(),
+            };
+
+            (error.abs(), nudge)
+        });
+
+        // Recalculate the least reliable component, just like in `point_to_pos`.
+        let [eq, er, es] = errors;
+        if eq > er && eq > es {
+            rounded[0] = -rounded[1] - rounded[2];
+        } else if er > es {
+            rounded[1] = -rounded[0] - rounded[2];
+        } else {
+            rounded[2] = -rounded[0] - rounded[1];
+        }
+
+        Cube::new_unchecked(
+            from[0] + rounded[0] as i64,
+            from[1] + rounded[1] as i64,
+            from[2] + rounded[2] as i64,
+        )
+    }
+
+    // Find the largest absolute value of cube coordinate components, i.e. the ring index.
+    pub(crate) fn abs_largest(&self) -> u64 {
+        [self.q, self.r, self.s]
             .into_iter()
+            .map(i64::unsigned_abs)
             .max()
             .unwrap()
     }
 
-    // Find the sum of cube coordinate components.
-    fn component_sum(&self) -> i32 {
-        self.q + self.r + self.s
+    // Find the sum of cube coordinate components; it's widened so that it can't overflow.
+    fn component_sum(&self) -> i128 {
+        self.q as i128 + self.r as i128 + self.s as i128
+    }
+}
+
+impl Add for Cube {
+    type Output = Cube;
+
+    fn add(self, other: Cube) -> Cube {
+        Cube::new_unchecked(self.q + other.q, self.r + other.r, self.s + other.s)
+    }
+}
+
+impl Sub for Cube {
+    type Output = Cube;
+
+    fn sub(self, other: Cube) -> Cube {
+        Cube::new_unchecked(self.q - other.q, self.r - other.r, self.s - other.s)
+    }
+}
+
+impl Neg for Cube {
+    type Output = Cube;
+
+    fn neg(self) -> Cube {
+        Cube::new_unchecked(-self.q, -self.r, -self.s)
+    }
+}
+
+impl Mul<i64> for Cube {
+    type Output = Cube;
+
+    fn mul(self, factor: i64) -> Cube {
+        Cube::new_unchecked(self.q * factor, self.r * factor, self.s * factor)
+    }
+}
+
+// The unvalidated form of a deserialized `Cube`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "Cube")]
+struct CubeRepr {
+    q: i64,
+    r: i64,
+    s: i64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CubeRepr> for Cube {
+    type Error = HexError;
+
+    fn try_from(repr: CubeRepr) -> Result<Self, Self::Error> {
+        Cube::new(repr.q, repr.r, repr.s)
+    }
+}
+
+/// The unit vector in the given direction, i.e. the cube coordinates of the origin's neighbor.
+impl From<Direction> for Cube {
+    fn from(dir: Direction) -> Self {
+        match dir {
+            Direction::N => Cube::new_unchecked(0, -1, 1),
+            Direction::NE => Cube::new_unchecked(1, -1, 0),
+            Direction::SE => Cube::new_unchecked(1, 0, -1),
+            Direction::S => Cube::new_unchecked(0, 1, -1),
+            Direction::SW => Cube::new_unchecked(-1, 1, 0),
+            Direction::NW => Cube::new_unchecked(-1, 0, 1),
+        }
+    }
+}
+
+/// Axial coordinate system for hex grid; it's the cube coordinate system with the implied `s`
+/// component omitted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Axial {
+    pub q: i64,
+    pub r: i64,
+}
+
+impl Axial {
+    /// Creates a new `Axial` from its components.
+    pub const fn new(q: i64, r: i64) -> Self {
+        Axial { q, r }
+    }
+}
+
+impl From<Cube> for Axial {
+    fn from(cube: Cube) -> Self {
+        Axial::new(cube.q, cube.r)
+    }
+}
+
+/// Fails if the implied `s` component doesn't fit in an `i64`.
+impl TryFrom<Axial> for Cube {
+    type Error = HexError;
+
+    fn try_from(axial: Axial) -> Result<Self, Self::Error> {
+        let s = -(axial.q as i128) - axial.r as i128;
+        let s = i64::try_from(s).map_err(|_| HexError::Overflow)?;
+
+        Ok(Cube::new_unchecked(axial.q, axial.r, s))
+    }
+}
+
+macro_rules! impl_axial_pos_conversions {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Axial {
+                fn from(pos: $t) -> Self {
+                    spiral_to_cube(pos).into()
+                }
+            }
+
+            impl TryFrom<Axial> for $t {
+                type Error = HexError;
+
+                fn try_from(axial: Axial) -> Result<Self, Self::Error> {
+                    cube_to_spiral(Cube::try_from(axial)?)
+                }
+            }
+        )*
+    };
+}
+
+impl_axial_pos_conversions!(u8, u16, u32, u64, u128, usize);
+
+impl Add for Axial {
+    type Output = Axial;
+
+    fn add(self, other: Axial) -> Axial {
+        Axial::new(self.q + other.q, self.r + other.r)
+    }
+}
+
+impl Sub for Axial {
+    type Output = Axial;
+
+    fn sub(self, other: Axial) -> Axial {
+        Axial::new(self.q - other.q, self.r - other.r)
+    }
+}
+
+impl Neg for Axial {
+    type Output = Axial;
+
+    fn neg(self) -> Axial {
+        Axial::new(-self.q, -self.r)
+    }
+}
+
+impl Mul<i64> for Axial {
+    type Output = Axial;
+
+    fn mul(self, factor: i64) -> Axial {
+        Axial::new(self.q * factor, self.r * factor)
+    }
+}
+
+/// The kinds of offset coordinates, depending on whether columns (`q`) or rows (`r`) are offset,
+/// and whether the odd or the even ones are shoved by half a hex. The column variants describe
+/// flat-topped grids like this one, while the row variants are meant for pointy-topped ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OffsetKind {
+    /// Odd columns are shoved down.
+    OddQ,
+    /// Even columns are shoved down.
+    EvenQ,
+    /// Odd rows are shoved right.
+    OddR,
+    /// Even rows are shoved right.
+    EvenR,
+}
+
+/// Offset coordinate system for rectangular hex grids.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Offset {
+    pub col: i64,
+    pub row: i64,
+}
+
+impl Offset {
+    /// Creates a new `Offset` from its components.
+    pub const fn new(col: i64, row: i64) -> Self {
+        Offset { col, row }
+    }
+
+    /// Converts the given cube coordinate to an offset one of the chosen kind; fails if any of
+    /// its components doesn't fit in an `i64`.
+    pub fn from_cube(cube: Cube, kind: OffsetKind) -> Result<Self, HexError> {
+        let Cube { q, r, .. } = cube;
+
+        let offset = match kind {
+            OffsetKind::OddQ => r.checked_add(floor_half(q)).map(|row| Offset::new(q, row)),
+            OffsetKind::EvenQ => r.checked_add(ceil_half(q)).map(|row| Offset::new(q, row)),
+            OffsetKind::OddR => q.checked_add(floor_half(r)).map(|col| Offset::new(col, r)),
+            OffsetKind::EvenR => q.checked_add(ceil_half(r)).map(|col| Offset::new(col, r)),
+        };
+
+        offset.ok_or(HexError::Overflow)
+    }
+
+    /// Converts the offset coordinate of the given kind to a cube one; fails if any of its
+    /// components doesn't fit in an `i64`.
+    pub fn to_cube(self, kind: OffsetKind) -> Result<Cube, HexError> {
+        let Offset { col, row } = self;
+
+        let axial = match kind {
+            OffsetKind::OddQ => row.checked_sub(floor_half(col)).map(|r| Axial::new(col, r)),
+            OffsetKind::EvenQ => row.checked_sub(ceil_half(col)).map(|r| Axial::new(col, r)),
+            OffsetKind::OddR => col.checked_sub(floor_half(row)).map(|q| Axial::new(q, row)),
+            OffsetKind::EvenR => col.checked_sub(ceil_half(row)).map(|q| Axial::new(q, row)),
+        };
+
+        Cube::try_from(axial.ok_or(HexError::Overflow)?)
+    }
+}
+
+// These equal (a - (a & 1)) / 2 and (a + (a & 1)) / 2 respectively, i.e. the shifts of the odd
+// and even columns or rows of offset coordinates, but can't overflow.
+fn floor_half(a: i64) -> i64 {
+    a.div_euclid(2)
+}
+
+fn ceil_half(a: i64) -> i64 {
+    a.div_euclid(2) + (a & 1)
+}
+
+/// The kinds of doubled coordinates, depending on which of the components is doubled. The
+/// doubled height variant describes flat-topped grids like this one, while the doubled width
+/// one is meant for pointy-topped ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DoubledKind {
+    /// Rows are doubled; the row step between vertical neighbors is 2.
+    Height,
+    /// Columns are doubled; the column step between horizontal neighbors is 2.
+    Width,
+}
+
+/// Doubled coordinate system for hex grids, in which `col + row` is always even; this is also
+/// checked when it's deserialized.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "DoubledRepr"))]
+pub struct Doubled {
+    pub col: i64,
+    pub row: i64,
+}
+
+impl Doubled {
+    /// Creates a new `Doubled` from its components.
+    pub const fn new(col: i64, row: i64) -> Self {
+        Doubled { col, row }
+    }
+
+    /// Converts the given cube coordinate to a doubled one of the chosen kind; fails if the
+    /// doubled component doesn't fit in an `i64`.
+    pub fn from_cube(cube: Cube, kind: DoubledKind) -> Result<Self, HexError> {
+        let Cube { q, r, .. } = cube;
+
+        let twice_plus = |a: i64, b: i64| {
+            i64::try_from(2 * a as i128 + b as i128).map_err(|_| HexError::Overflow)
+        };
+
+        match kind {
+            DoubledKind::Height => Ok(Doubled::new(q, twice_plus(r, q)?)),
+            DoubledKind::Width => Ok(Doubled::new(twice_plus(q, r)?, r)),
+        }
+    }
+
+    /// Converts the doubled coordinate of the given kind to a cube one; fails if `col + row`
+    /// is odd, as such a coordinate doesn't point at any hex, or if the implied component
+    /// doesn't fit in an `i64`.
+    pub fn to_cube(self, kind: DoubledKind) -> Result<Cube, HexError> {
+        let Doubled { col, row } = self;
+
+        if col & 1 != row & 1 {
+            return Err(HexError::InvalidDoubled { col, row });
+        }
+
+        // With both components having the same parity, this equals (row - col) / 2 exactly,
+        // but can't overflow.
+        let half_diff = |a: i64, b: i64| a.div_euclid(2) - b.div_euclid(2);
+
+        let axial = match kind {
+            DoubledKind::Height => Axial::new(col, half_diff(row, col)),
+            DoubledKind::Width => Axial::new(half_diff(col, row), row),
+        };
+
+        Cube::try_from(axial)
+    }
+}
+
+// The unvalidated form of a deserialized `Doubled`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "Doubled")]
+struct DoubledRepr {
+    col: i64,
+    row: i64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<DoubledRepr> for Doubled {
+    type Error = HexError;
+
+    fn try_from(DoubledRepr { col, row }: DoubledRepr) -> Result<Self, Self::Error> {
+        if col & 1 != row & 1 {
+            return Err(HexError::InvalidDoubled { col, row });
+        }
+
+        Ok(Doubled::new(col, row))
     }
 }
 
 /// Convert spiral hex coordinate x to cube coords (q,r,s).
 /// See: https://www.redblobgames.com/grids/hexagons/
 /// for a definition of cube coords.
-pub fn spiral_to_cube(x: usize) -> Cube {
+///
+/// Panics if the ring index doesn't fit in an `i64`, which is only possible for `u128`
+/// positions; see [`checked_spiral_to_cube`].
+pub fn spiral_to_cube<P: PosInt>(x: P) -> Cube {
+    checked_spiral_to_cube(x).expect("ring index overflow")
+}
+
+/// Convert spiral hex coordinate x to cube coords (q,r,s), or return `None` if its ring index
+/// doesn't fit in an `i64`.
+pub fn checked_spiral_to_cube<P: PosInt>(x: P) -> Option<Cube> {
     // The origin is a special case: return (0,0,0)
-    if x == 0 {
-        return Cube::default();
+    if x.is_zero() {
+        return Some(Cube::default());
     }
 
-    // Find the ring index and ring-offset for this spiral
-    let ring_index = ring(x) as f32;
-    let ring_offset = ring_offset(ring_index as usize) as f32;
-
-    // Calculate q and r
-    let q = growing_trunc_tri(x as f32, ring_index, ring_offset, 0.0);
-    let r = growing_trunc_tri(x as f32, ring_index, ring_offset, 4.0);
+    // Find the ring, the edge and the distance from the tip the edge starts at
+    let ring_index = ring(x);
+    let edge_index = ring_edge_index(x);
+    let edge: P = (edge_index as u8).into();
+    let tip_offset = x - ring_offset(ring_index) - edge * ring_index;
 
-    // Could alternatively manually calculate s as:
-    // let s = growing_trunc_tri(x, ring_offset, p, ring_index, -4.0);
-    let s = -q - r;
+    // The tip lies `ring_index` steps away from the origin in the direction of the edge index,
+    // and the edge then runs clockwise, i.e. 2 directions further.
+    let tip_dir = Direction::ALL[edge_index];
+    let edge_dir = tip_dir.rotate_cw().rotate_cw();
 
-    Cube::new(q, r, s)
+    Some(Cube::from(tip_dir) * ring_index.to_i64()? + Cube::from(edge_dir) * tip_offset.to_i64()?)
 }
 
 /// Calculate a spiral hex coordinate for an input (q,r,s) in cube coordinates.
-pub fn cube_to_spiral(coord: Cube) -> Result<usize, &'static str> {
+pub fn cube_to_spiral<P: PosInt>(coord: Cube) -> Result<P, HexError> {
     // The origin is a special case, return 0.
     if coord == Cube::default() {
-        return Ok(0);
+        return Ok(P::zero());
     }
 
     // Make sure we've been passed a valid cube coordinate. The components should sum to 0.
     if coord.component_sum() != 0 {
-        return Err("q + r + s != 0");
+        let Cube { q, r, s } = coord;
+        return Err(HexError::InvalidCubeSum { q, r, s });
     }
 
     // Find the ring index based on the maximum absolute value of q, r or s.
-    let ring_index = coord.abs_largest() as usize;
-
-    let ring_offset = ring_offset(ring_index);
-
-    // We now know approximately where we are in the truncated triangle wave.
-    // If we start at x = ring_offset and calculate q,r,s values from this point up to
-    // x = (ring_offset + ring_index * 6), we should find matching q, r, s values for some value of x.
-
-    let x = ring_offset..(ring_offset + ring_index * 6);
-
-    match x
-        .into_iter()
-        .map(|v| (v, spiral_to_cube(v)))
-        .find(|(_, c)| *c == coord)
-        .map(|(x, _)| x)
-    {
-        Some(value) => Ok(value),
-        None => Err("Couldn't find a solution"),
-    }
+    let ring_index = i64::try_from(coord.abs_largest()).map_err(|_| HexError::Overflow)?;
+    let Cube { q, r, s } = coord;
+
+    // Every edge is a straight line between two tips, so the edge can be identified by the
+    // component fixed at the ring index; the tip at which the edge ends belongs to the next one.
+    let (edge_index, tip_offset) = if r == -ring_index && s > 0 {
+        (0, q)
+    } else if q == ring_index && r < 0 {
+        (1, -s)
+    } else if s == -ring_index && q > 0 {
+        (2, r)
+    } else if r == ring_index && s < 0 {
+        (3, -q)
+    } else if q == -ring_index && r > 0 {
+        (4, s)
+    } else {
+        (5, -r)
+    };
+
+    // The components are all within the ring index, so only the final position can overflow.
+    let ring_index: P = num_traits::cast(ring_index).ok_or(HexError::Overflow)?;
+    let tip_offset: P = num_traits::cast(tip_offset).ok_or(HexError::Overflow)?;
+    let edge_index: P = (edge_index as u8).into();
+
+    checked_ring_offset(ring_index)
+        .and_then(|offset| offset.checked_add(&edge_index.checked_mul(&ring_index)?))
+        .and_then(|pos| pos.checked_add(&tip_offset))
+        .ok_or(HexError::Overflow)
 }
 
-/// Calculates y = f(x) where f is a truncated triangle wave of initial period, p = 6, and amplitude, a = 1.5
-/// The amplitude and period increase each cycle.
-/// - c is the cycle number that we're currently on (i.e. c=1 for the first cycle, and so on)
-/// - x_prime is the value of x that this cycle began on
-/// - phi is a phase shift in the triangle wave
-fn growing_trunc_tri(x: f32, c: f32, x_prime: f32, phi: f32) -> i32 {
-    // The base period of the triangle wave during cycle 1 (the number of sides a hexagon has)
-    let p = 6.0;
-
-    // How far along we are in the current cycle
-    let offset_x = x - x_prime;
+/// Convert a spiral hex coordinate to an offset coordinate of the given kind.
+pub fn spiral_to_offset<P: PosInt>(pos: P, kind: OffsetKind) -> Offset {
+    // The components of the cube coordinates of positions lie within `-i64::MAX..=i64::MAX`, and
+    // then the offset ones are always representable.
+    Offset::from_cube(spiral_to_cube(pos), kind).expect("offset coordinate overflow")
+}
 
-    // We'll use the modulo version of the equation for a triangle wave
-    // https://en.wikipedia.org/wiki/Triangle_wave
-    // But we'll modify it so that the cycle number is used to multiply the amplitude and period,
-    // making the triangle wave get taller and broader each cyle. Define some params used in the calc:
-    let s = offset_x - (c / 4.0) * (2.0 * phi + p);
-    let p_star = c * p;
+/// Calculate a spiral hex coordinate for an offset coordinate of the given kind.
+pub fn offset_to_spiral<P: PosInt>(offset: Offset, kind: OffsetKind) -> Result<P, HexError> {
+    cube_to_spiral(offset.to_cube(kind)?)
+}
 
-    // Here y_1 = g(x), where g is the triangle wave before it's truncated
-    let y_1 = 6.0 / p * (modulo(s, p_star) - c * p / 2.0).abs() - 1.5 * (c);
+/// Convert a spiral hex coordinate to a doubled coordinate of the given kind; fails if any of its
+/// components doesn't fit in an `i64`.
+pub fn spiral_to_doubled<P: PosInt>(pos: P, kind: DoubledKind) -> Result<Doubled, HexError> {
+    let cube = checked_spiral_to_cube(pos).ok_or(HexError::Overflow)?;
 
-    // We now truncate the wave so that it never has an amplitude greater than the cycle number
-    match y_1.abs() > c {
-        true => (y_1.signum() * c) as i32,
-        false => y_1 as i32,
-    }
+    Doubled::from_cube(cube, kind)
 }
 
-/// In Rust, a % b finds the remainder of a / b. This function finds the actual modulo (not the remainder) of a and b.
-fn modulo<T: std::ops::Rem<Output = T> + std::ops::Add<Output = T> + Copy>(a: T, b: T) -> T {
-    ((a % b) + b) % b
+/// Calculate a spiral hex coordinate for a doubled coordinate of the given kind.
+pub fn doubled_to_spiral<P: PosInt>(doubled: Doubled, kind: DoubledKind) -> Result<P, HexError> {
+    cube_to_spiral(doubled.to_cube(kind)?)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::convert::{cube_to_spiral, spiral_to_cube, Cube};
+    use super::*;
     #[test]
     fn convert_spiral_to_cube() {
         // Test a few input values in spiral coordinates
@@ -138,7 +571,7 @@ mod tests {
         // This is the result we expect to get
         let expected = [(0, 0, 0), (0, -1, 1), (0, 1, -1), (0, -2, 2), (1,-2,1), (4, 0, -4)]
             .into_iter()
-            .map(|(q, r, s)| Cube::new(q, r, s))
+            .map(|(q, r, s)| Cube::new_unchecked(q, r, s))
             .collect::<Vec<Cube>>();
 
         assert_eq!(expected, result);
@@ -149,7 +582,7 @@ mod tests {
         // Test a few input values in cube coordinates
         let cube = [(0, 0, 0), (0, -1, 1), (0, 1, -1), (0, -2, 2), (1,-2,1), (4, 0, -4)]
             .into_iter()
-            .map(|(q, r, s)| Cube::new(q, r, s));
+            .map(|(q, r, s)| Cube::new_unchecked(q, r, s));
 
         // Try find their spiral coords
         let result = cube
@@ -163,7 +596,380 @@ mod tests {
     #[test]
     fn convert_invalid_qrs() {
         // An invalid set of cube coords
-        assert_eq!(Err("q + r + s != 0"), cube_to_spiral(Cube::new(-1, -1, 0)),)
+        assert_eq!(
+            Err(HexError::InvalidCubeSum { q: -1, r: -1, s: 0 }),
+            cube_to_spiral::<usize>(Cube::new_unchecked(-1, -1, 0)),
+        )
+    }
+
+    #[test]
+    fn convert_round_trip() {
+        for pos in 0..ring_offset(30usize) {
+            assert_eq!(cube_to_spiral(spiral_to_cube(pos)), Ok(pos));
+        }
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn convert_large_cube_to_spiral() {
+        for pos in [
+            ring_offset(1 << 30) - 1,
+            ring_offset(2_479_700_525),
+            usize::MAX - 1,
+            usize::MAX,
+        ] {
+            assert_eq!(cube_to_spiral(spiral_to_cube(pos)), Ok(pos));
+        }
+
+        // The position next to the final one is beyond the range of `usize`
+        let k = 2_479_700_525;
+        let tip_offset = (usize::MAX - ring_offset(k as usize)) as i64 + 1;
+        assert_eq!(
+            cube_to_spiral::<usize>(Cube::new_unchecked(tip_offset, -k, k - tip_offset)),
+            Err(HexError::Overflow)
+        );
+        assert_eq!(
+            cube_to_spiral::<usize>(Cube::new_unchecked(0, -(1 << 40), 1 << 40)),
+            Err(HexError::Overflow)
+        );
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn convert_large_spiral_to_cube() {
+        // The last (incomplete) ring representable with a `usize`
+        let ring = 2_479_700_525;
+        let offset = ring_offset(ring);
+        let k = ring as i64;
+
+        // Its first position, at the top tip
+        assert_eq!(spiral_to_cube(offset), Cube::new_unchecked(0, -k, k));
+
+        // The last position of the previous ring, just below it
+        assert_eq!(
+            spiral_to_cube(offset - 1),
+            Cube::new_unchecked(-1, 2 - k, k - 1)
+        );
+
+        // The final position; edge 0 of the ring runs south-east from the top tip
+        let tip_offset = (usize::MAX - offset) as i64;
+        assert_eq!(
+            spiral_to_cube(usize::MAX),
+            Cube::new_unchecked(tip_offset, -k, k - tip_offset)
+        );
+    }
+
+    #[test]
+    fn cube_constructors() {
+        let cube = Cube::new(3, -5, 2).unwrap();
+        assert_eq!((cube.q(), cube.r(), cube.s()), (3, -5, 2));
+        assert_eq!(cube, Cube::new_unchecked(3, -5, 2));
+
+        assert_eq!(
+            Cube::new(-1, -1, 0),
+            Err(HexError::InvalidCubeSum { q: -1, r: -1, s: 0 })
+        );
+        assert!(Cube::new(i64::MAX, i64::MAX, 2).is_err());
+        assert!(Cube::new(i64::MIN, i64::MAX, 1).is_ok());
+
+        // valid, but its ring is too large for a spiral position
+        assert_eq!(
+            cube_to_spiral::<usize>(Cube::new_unchecked(i64::MIN, i64::MAX, 1)),
+            Err(HexError::Overflow)
+        );
+    }
+
+    #[test]
+    fn cube_arithmetic() {
+        let a = spiral_to_cube(8usize);
+        let b = spiral_to_cube(4usize);
+
+        assert_eq!(a + b, Cube::new_unchecked(1, -1, 0));
+        assert_eq!(a - b, Cube::new_unchecked(1, -3, 2));
+        assert_eq!(-a, Cube::new_unchecked(-1, 2, -1));
+        assert_eq!(b * 3, spiral_to_cube(28usize));
+        assert_eq!(cube_to_spiral(a + b), Ok(2usize));
+
+        assert_eq!(a.distance(&b), 3);
+        assert_eq!(b.distance(&a), 3);
+        assert_eq!(a.distance(&a), 0);
+
+        let (top, bottom) = (
+            Cube::new_unchecked(0, i64::MIN + 1, i64::MAX),
+            Cube::new_unchecked(0, i64::MAX, i64::MIN + 1),
+        );
+        assert_eq!(top.distance(&bottom), u64::MAX - 1);
+    }
+
+    #[test]
+    fn axial_conversions() {
+        for pos in 0..ring_offset(10usize) {
+            let axial = Axial::from(pos);
+            let cube = spiral_to_cube(pos);
+
+            assert_eq!((axial.q, axial.r), (cube.q(), cube.r()));
+            assert_eq!(Cube::try_from(axial), Ok(cube));
+            assert_eq!(Axial::from(cube), axial);
+            assert_eq!(usize::try_from(axial), Ok(pos));
+        }
+
+        assert_eq!(
+            usize::try_from(Axial::new(0, i64::MIN + 1)),
+            Err(HexError::Overflow)
+        );
+        assert_eq!(
+            Cube::try_from(Axial::new(i64::MIN, 1)),
+            Ok(Cube::new_unchecked(i64::MIN, 1, i64::MAX))
+        );
+        assert_eq!(
+            Cube::try_from(Axial::new(i64::MIN, 0)),
+            Err(HexError::Overflow)
+        );
+        assert_eq!(
+            usize::try_from(Axial::new(i64::MIN, 0)),
+            Err(HexError::Overflow)
+        );
     }
-}
 
+    #[test]
+    fn axial_arithmetic() {
+        let a = Axial::from(8usize);
+        let b = Axial::from(4usize);
+
+        assert_eq!(a + b, Axial::from(2usize));
+        assert_eq!(a - b, Axial::new(1, -3));
+        assert_eq!(-a, Axial::new(-1, 2));
+        assert_eq!(b * 3, Axial::from(28usize));
+        assert_eq!(
+            Cube::try_from(a - b).unwrap(),
+            spiral_to_cube(8usize) - spiral_to_cube(4usize)
+        );
+    }
+
+    #[test]
+    fn offset_conversions() {
+        use OffsetKind::*;
+
+        // The north-east and south-east neighbors of the origin, and the one below the latter
+        assert_eq!(spiral_to_offset(2usize, OddQ), Offset::new(1, -1));
+        assert_eq!(spiral_to_offset(3usize, OddQ), Offset::new(1, 0));
+        assert_eq!(spiral_to_offset(12usize, OddQ), Offset::new(1, 1));
+        assert_eq!(spiral_to_offset(2usize, EvenQ), Offset::new(1, 0));
+        assert_eq!(spiral_to_offset(3usize, EvenQ), Offset::new(1, 1));
+        assert_eq!(spiral_to_offset(2usize, OddR), Offset::new(0, -1));
+        assert_eq!(spiral_to_offset(2usize, EvenR), Offset::new(1, -1));
+
+        for kind in [OddQ, EvenQ, OddR, EvenR] {
+            for pos in 0..ring_offset(10usize) {
+                let offset = spiral_to_offset(pos, kind);
+
+                assert_eq!(offset.to_cube(kind), Ok(spiral_to_cube(pos)));
+                assert_eq!(offset_to_spiral(offset, kind), Ok(pos));
+            }
+        }
+
+        assert_eq!(
+            Offset::new(i64::MAX, 0).to_cube(EvenQ),
+            Ok(Cube::new_unchecked(
+                i64::MAX,
+                -(1 << 62),
+                (1 << 62) - i64::MAX
+            ))
+        );
+        assert_eq!(
+            Offset::new(i64::MIN, i64::MAX).to_cube(OddQ),
+            Err(HexError::Overflow)
+        );
+        assert_eq!(
+            offset_to_spiral::<usize>(Offset::new(i64::MIN, i64::MAX), OddQ),
+            Err(HexError::Overflow)
+        );
+
+        // the extreme cube coordinates are converted without overflow, unless the result can't be
+        // represented, which is never the case for the spiral positions
+        let k = i64::MAX as u128;
+        for kind in [OddQ, EvenQ, OddR, EvenR] {
+            for pos in [
+                ring_offset(k),
+                ring_offset(k) + 3 * k,
+                ring_offset(k + 1) - 1,
+            ] {
+                assert_eq!(offset_to_spiral(spiral_to_offset(pos, kind), kind), Ok(pos));
+            }
+
+            for cube in [
+                Cube::new_unchecked(i64::MIN + 1, i64::MAX, 0),
+                Cube::new_unchecked(i64::MAX, i64::MIN + 1, 0),
+                Cube::new_unchecked(i64::MAX, 0, -i64::MAX),
+            ] {
+                let offset = Offset::from_cube(cube, kind);
+                assert_eq!(offset.and_then(|offset| offset.to_cube(kind)), Ok(cube));
+            }
+        }
+        assert_eq!(
+            Offset::from_cube(Cube::new_unchecked(i64::MIN + 1, i64::MAX, 0), EvenR),
+            Ok(Offset::new(i64::MIN + 1 + (1 << 62), i64::MAX))
+        );
+        assert_eq!(
+            spiral_to_offset(ring_offset(k) + 3 * k, EvenR),
+            Offset::new(1 << 62, i64::MAX)
+        );
+        assert_eq!(
+            Offset::from_cube(Cube::new_unchecked(1, i64::MAX, i64::MIN), EvenQ),
+            Err(HexError::Overflow)
+        );
+    }
+
+    #[test]
+    fn offset_rectangle() {
+        use OffsetKind::*;
+
+        for kind in [OddQ, EvenQ, OddR, EvenR] {
+            let mut poss = Vec::new();
+
+            for row in -4..=4 {
+                for col in -4..=4 {
+                    let offset = Offset::new(col, row);
+                    let pos: usize = offset_to_spiral(offset, kind).unwrap();
+                    assert_eq!(spiral_to_offset(pos, kind), offset);
+                    poss.push(pos);
+                }
+            }
+
+            poss.sort_unstable();
+            poss.dedup();
+            assert_eq!(poss.len(), 81);
+        }
+    }
+
+    #[test]
+    fn doubled_conversions() {
+        use DoubledKind::*;
+
+        // The north, north-east and south-east neighbors of the origin
+        assert_eq!(spiral_to_doubled(1usize, Height), Ok(Doubled::new(0, -2)));
+        assert_eq!(spiral_to_doubled(2usize, Height), Ok(Doubled::new(1, -1)));
+        assert_eq!(spiral_to_doubled(3usize, Height), Ok(Doubled::new(1, 1)));
+        assert_eq!(spiral_to_doubled(1usize, Width), Ok(Doubled::new(-1, -1)));
+        assert_eq!(spiral_to_doubled(2usize, Width), Ok(Doubled::new(1, -1)));
+        assert_eq!(spiral_to_doubled(3usize, Width), Ok(Doubled::new(2, 0)));
+
+        for kind in [Height, Width] {
+            for pos in 0..ring_offset(10usize) {
+                let doubled = spiral_to_doubled(pos, kind).unwrap();
+                assert_eq!((doubled.col + doubled.row) % 2, 0);
+                assert_eq!(doubled.to_cube(kind), Ok(spiral_to_cube(pos)));
+                assert_eq!(Doubled::from_cube(spiral_to_cube(pos), kind), Ok(doubled));
+                assert_eq!(doubled_to_spiral(doubled, kind), Ok(pos));
+            }
+
+            assert_eq!(
+                doubled_to_spiral::<usize>(Doubled::new(1, 0), kind),
+                Err(HexError::InvalidDoubled { col: 1, row: 0 })
+            );
+        }
+
+        assert_eq!(
+            Doubled::new(i64::MIN, i64::MIN).to_cube(DoubledKind::Height),
+            Err(HexError::Overflow)
+        );
+
+        // the doubled component of the extreme cube coordinates may not be representable
+        let cube = Cube::new(0, i64::MAX, -i64::MAX).unwrap();
+        assert_eq!(Doubled::from_cube(cube, Height), Err(HexError::Overflow));
+        assert_eq!(
+            Doubled::from_cube(cube, Width),
+            Ok(Doubled::new(i64::MAX, i64::MAX))
+        );
+        assert_eq!(
+            Doubled::from_cube(Cube::new_unchecked(-1, 1 << 62, 0), Height),
+            Ok(Doubled::new(-1, i64::MAX))
+        );
+
+        let k = 1u128 << 62;
+        assert_eq!(
+            spiral_to_doubled(ring_offset(k), Height),
+            Ok(Doubled::new(0, i64::MIN))
+        );
+        assert_eq!(
+            spiral_to_doubled(ring_offset(k) + 3 * k, Height),
+            Err(HexError::Overflow)
+        );
+        assert_eq!(spiral_to_doubled(u128::MAX, Width), Err(HexError::Overflow));
+    }
+
+    #[test]
+    fn direction_vectors() {
+        for dir in Direction::iter() {
+            assert_eq!(Cube::from(dir), spiral_to_cube(dir.index() + 1));
+            assert_eq!(Cube::from(dir.opposite()), -Cube::from(dir));
+        }
+    }
+
+    #[test]
+    fn convert_widths() {
+        for pos in 0..ring_offset(10usize) {
+            let cube = spiral_to_cube(pos);
+
+            assert_eq!(spiral_to_cube(pos as u16), cube);
+            assert_eq!(spiral_to_cube(pos as u128), cube);
+            assert_eq!(cube_to_spiral(cube), Ok(pos as u32));
+            assert_eq!(u64::try_from(Axial::from(cube)), Ok(pos as u64));
+        }
+
+        // The last ring representable with an `i64` can be reached with `u128` positions
+        let k = i64::MAX;
+        let pos = ring_offset(k as u128);
+        assert_eq!(spiral_to_cube(pos), Cube::new_unchecked(0, -k, k));
+        assert_eq!(cube_to_spiral(Cube::new_unchecked(0, -k, k)), Ok(pos));
+        assert_eq!(checked_spiral_to_cube(u128::MAX), None);
+
+        assert_eq!(
+            cube_to_spiral::<u8>(spiral_to_cube(ring_offset(10u16))),
+            Err(HexError::Overflow)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let cube = spiral_to_cube(45usize);
+        let json = serde_json::to_string(&cube).unwrap();
+        assert_eq!(json, r#"{"q":4,"r":0,"s":-4}"#);
+        assert_eq!(serde_json::from_str::<Cube>(&json).unwrap(), cube);
+
+        let err = serde_json::from_str::<Cube>(r#"{"q":1,"r":1,"s":1}"#).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(&HexError::InvalidCubeSum { q: 1, r: 1, s: 1 }.to_string()));
+
+        let axial = Axial::from(cube);
+        let json = serde_json::to_string(&axial).unwrap();
+        assert_eq!(serde_json::from_str::<Axial>(&json).unwrap(), axial);
+
+        let offset = (
+            Offset::from_cube(cube, OffsetKind::EvenR).unwrap(),
+            OffsetKind::EvenR,
+        );
+        let json = serde_json::to_string(&offset).unwrap();
+        assert_eq!(
+            serde_json::from_str::<(Offset, OffsetKind)>(&json).unwrap(),
+            offset
+        );
+
+        let doubled = (
+            Doubled::from_cube(cube, DoubledKind::Width).unwrap(),
+            DoubledKind::Width,
+        );
+        let json = serde_json::to_string(&doubled).unwrap();
+        assert_eq!(
+            serde_json::from_str::<(Doubled, DoubledKind)>(&json).unwrap(),
+            doubled
+        );
+
+        let err = serde_json::from_str::<Doubled>(r#"{"col":1,"row":2}"#).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(&HexError::InvalidDoubled { col: 1, row: 2 }.to_string()));
+    }
+}
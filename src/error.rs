@@ -0,0 +1,45 @@
+//! The error type returned by the fallible functions of the crate.
+
+use core::fmt;
+
+/// An error returned by the fallible functions of the crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HexError {
+    /// The components of a cube coordinate don't sum to 0.
+    InvalidCubeSum { q: i64, r: i64, s: i64 },
+    /// The components of a doubled coordinate don't sum to an even number.
+    InvalidDoubled { col: i64, row: i64 },
+    /// The result can't be represented by the numeric type it's calculated in.
+    Overflow,
+    /// The hex lies in a ring beyond the allowed one.
+    RingOutOfBounds { ring: u128, max_ring: u128 },
+    /// The index doesn't correspond to any of the 6 directions.
+    InvalidDirection(usize),
+    /// The point has a non-finite coordinate, or the layout it's placed in is non-finite or has
+    /// a zero size.
+    InvalidPoint,
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCubeSum { q, r, s } => {
+                write!(f, "invalid cube coordinate ({q}, {r}, {s}): q + r + s != 0")
+            }
+            Self::InvalidDoubled { col, row } => {
+                write!(
+                    f,
+                    "invalid doubled coordinate ({col}, {row}): col + row is odd"
+                )
+            }
+            Self::Overflow => write!(f, "the result is too large to be represented"),
+            Self::RingOutOfBounds { ring, max_ring } => {
+                write!(f, "ring {ring} is beyond the maximum ring {max_ring}")
+            }
+            Self::InvalidDirection(idx) => write!(f, "invalid direction index {idx}"),
+            Self::InvalidPoint => write!(f, "the point or its layout is invalid"),
+        }
+    }
+}
+
+impl core::error::Error for HexError {}
@@ -1,3 +1,13 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod convention;
 pub mod convert;
+pub mod error;
+#[cfg(all(feature = "alloc", any(feature = "std", feature = "libm")))]
+pub mod mesh;
+#[cfg(any(feature = "std", feature = "libm"))]
 pub mod point;
 pub mod position;
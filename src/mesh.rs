@@ -0,0 +1,382 @@
+//! Build vertex and index buffers of hex maps, which can be fed to any graphics backend, or
+//! export them as 3D models.
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+#[cfg(feature = "std")]
+use std::{collections::HashMap, io};
+
+use num_traits::{Float, FloatConst};
+
+#[cfg(feature = "std")]
+use crate::position::{checked_neighboring_positions, Direction};
+use crate::{convert::spiral_to_cube, point::Layout, position::PosInt};
+
+// The offsets of the corners of a hex from 3 times its axial coordinates, in the order of
+// `Layout::corners`; they make the coordinates of the corners shared by neighboring hexes equal.
+const CORNER_OFFSETS: [(i128, i128); 6] = [(2, -1), (1, 1), (-1, 2), (-2, 1), (-1, -1), (1, -2)];
+
+// The corners of a hex the triangles of its fan consist of.
+const TRIANGLES: [[usize; 3]; 4] = [[0, 1, 2], [0, 2, 3], [0, 3, 4], [0, 4, 5]];
+
+/// The options used to build a [`HexMesh`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MeshOptions {
+    /// Whether the corners shared by neighboring hexes with equal attributes should become a
+    /// single vertex.
+    pub dedup: bool,
+    /// Whether the outlines of the hexes should be built too.
+    pub outline: bool,
+}
+
+/// The vertex and index buffers of a set of hexes.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HexMesh<T, F = f32> {
+    /// The pixel coordinates of the vertices.
+    pub vertices: Vec<[F; 2]>,
+    /// The attributes of the vertices, inherited from their hexes; there's one per vertex.
+    pub attributes: Vec<T>,
+    /// A triangle list consisting of 4 triangles per hex, clockwise on the screen.
+    pub indices: Vec<u32>,
+    /// A line list with the edges of the hexes, if requested; the edges shared by neighboring
+    /// hexes are only listed once if their vertices are deduplicated.
+    pub outline: Option<Vec<u32>>,
+}
+
+impl<T: Clone + PartialEq, F: Float + FloatConst> HexMesh<T, F> {
+    /// Builds the mesh of the hexes at the given positions, each with its own attribute, e.g. a
+    /// color or an elevation; the positions are expected to be unique.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of vertices exceeds `u32::MAX`.
+    pub fn new<P: PosInt>(
+        hexes: impl IntoIterator<Item = (P, T)>,
+        layout: &Layout<F>,
+        options: MeshOptions,
+    ) -> Self {
+        let mut mesh = HexMesh {
+            vertices: Vec::new(),
+            attributes: Vec::new(),
+            indices: Vec::new(),
+            outline: options.outline.then(Vec::new),
+        };
+        // The indices of the vertices created at the given corners.
+        let mut shared_corners: BTreeMap<(i128, i128), Vec<u32>> = BTreeMap::new();
+        // The edges that were already outlined, as sorted pairs of vertex indices.
+        let mut outlined_edges: BTreeSet<(u32, u32)> = BTreeSet::new();
+
+        for (pos, attribute) in hexes {
+            let cube = spiral_to_cube(pos);
+            let corners = layout.corners(pos);
+            let mut hex_indices = [0u32; 6];
+
+            for (i, (x, y)) in corners.into_iter().enumerate() {
+                let key = (
+                    3 * cube.q() as i128 + CORNER_OFFSETS[i].0,
+                    3 * cube.r() as i128 + CORNER_OFFSETS[i].1,
+                );
+
+                let existing = options
+                    .dedup
+                    .then(|| shared_corners.get(&key))
+                    .flatten()
+                    .and_then(|indices| {
+                        indices
+                            .iter()
+                            .copied()
+                            .find(|&idx| mesh.attributes[idx as usize] == attribute)
+                    });
+
+                hex_indices[i] = existing.unwrap_or_else(|| {
+                    let idx = u32::try_from(mesh.vertices.len()).expect("too many vertices");
+                    mesh.vertices.push([x, y]);
+                    mesh.attributes.push(attribute.clone());
+                    if options.dedup {
+                        shared_corners.entry(key).or_default().push(idx);
+                    }
+                    idx
+                });
+            }
+
+            for triangle in TRIANGLES {
+                mesh.indices
+                    .extend(triangle.into_iter().map(|corner| hex_indices[corner]));
+            }
+
+            if let Some(outline) = &mut mesh.outline {
+                for i in 0..6 {
+                    let (a, b) = (hex_indices[i], hex_indices[(i + 1) % 6]);
+
+                    if outlined_edges.insert((a.min(b), a.max(b))) {
+                        outline.extend([a, b]);
+                    }
+                }
+            }
+        }
+
+        mesh
+    }
+}
+
+/// Writes a Wavefront OBJ model of the hexes at the given positions, extruded from 0 up to the
+/// given heights. Every hex has a top face and side walls where its neighbors are lower, with the
+/// missing ones considered to be at height 0. The pixel coordinates `(x, y)` of the layout
+/// become `(x, height, y)`, so that the y axis points up and the map seen from above looks like
+/// on the screen; the faces are counter-clockwise when seen from the outside.
+#[cfg(feature = "std")]
+pub fn write_obj<P: PosInt, F: Float + FloatConst>(
+    heights: &HashMap<P, F>,
+    layout: &Layout<F>,
+    mut writer: impl io::Write,
+) -> io::Result<()> {
+    let mut poss = heights.keys().copied().collect::<Vec<_>>();
+    poss.sort_unstable();
+
+    let height_at = |pos: Option<P>| {
+        pos.and_then(|pos| heights.get(&pos))
+            .copied()
+            .unwrap_or_else(F::zero)
+    };
+    let mut vertex_count = 0usize;
+    let mut write_face = |writer: &mut dyn io::Write, vertices: &[((F, F), F)]| {
+        for ((x, y), height) in vertices {
+            writeln!(
+                writer,
+                "v {} {} {}",
+                x.to_f64().unwrap(),
+                height.to_f64().unwrap(),
+                y.to_f64().unwrap()
+            )?;
+        }
+
+        write!(writer, "f")?;
+        for _ in vertices {
+            vertex_count += 1;
+            write!(writer, " {vertex_count}")?;
+        }
+        writeln!(writer)
+    };
+
+    writeln!(writer, "# hex-spiral terrain")?;
+
+    for pos in poss {
+        let height = height_at(Some(pos));
+        let corners = layout.corners(pos);
+        let neighbors = checked_neighboring_positions(pos);
+
+        // The corners are clockwise when seen from above, so the top face needs them reversed.
+        let top = corners.map(|corner| (corner, height));
+        write_face(
+            &mut writer,
+            &[top[0], top[5], top[4], top[3], top[2], top[1]],
+        )?;
+
+        for dir in Direction::iter() {
+            let neighbor_height = height_at(neighbors.map(|neighbors| neighbors[dir.index()]));
+
+            if neighbor_height >= height {
+                continue;
+            }
+
+            // The edge shared with the neighbor in the given direction, clockwise.
+            let a = corners[(dir.index() + 4) % 6];
+            let b = corners[(dir.index() + 5) % 6];
+            let wall = [
+                (a, height),
+                (b, height),
+                (b, neighbor_height),
+                (a, neighbor_height),
+            ];
+            write_face(&mut writer, &wall)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        point::Orientation,
+        position::{ring_offset, Pos},
+    };
+
+    #[test]
+    fn mesh_sizes() {
+        let layout = Layout::new((10.0, 10.0), (0.0, 0.0), Orientation::Flat);
+        let hexes = |n: Pos| (0..n).map(|pos| (pos, ()));
+
+        let mesh = HexMesh::new(hexes(7), &layout, MeshOptions::default());
+        assert_eq!(mesh.vertices.len(), 6 * 7);
+        assert_eq!(mesh.attributes.len(), 6 * 7);
+        assert_eq!(mesh.indices.len(), 12 * 7);
+        assert_eq!(mesh.outline, None);
+
+        // a hex surrounded by a ring has 6 + 6 * 3 distinct corners and 6 + 6 * 4 distinct edges
+        let options = MeshOptions {
+            dedup: true,
+            outline: true,
+        };
+        let mesh = HexMesh::new(hexes(7), &layout, options);
+        assert_eq!(mesh.vertices.len(), 24);
+        assert_eq!(mesh.indices.len(), 12 * 7);
+        assert_eq!(mesh.outline.as_ref().unwrap().len(), 2 * 30);
+
+        // every further ring k, of 6 * k hexes, adds 12 * k + 6 corners
+        let mesh = HexMesh::new(hexes(ring_offset(5)), &layout, options);
+        assert_eq!(mesh.vertices.len(), 6 * 5 * 5);
+    }
+
+    #[test]
+    fn mesh_geometry() {
+        let options = MeshOptions {
+            dedup: true,
+            outline: true,
+        };
+
+        for orientation in [Orientation::Flat, Orientation::Pointy] {
+            let layout = Layout::new((4.0, 3.0), (50.0, 50.0), orientation).with_rotation(0.3);
+            let mesh = HexMesh::new(
+                (0..ring_offset(3usize)).map(|pos| (pos, ())),
+                &layout,
+                options,
+            );
+
+            for (pos, triangles) in mesh.indices.chunks(12).enumerate() {
+                let corners = layout.corners(pos);
+
+                for (triangle, expected) in triangles.chunks(3).zip(TRIANGLES) {
+                    for (idx, corner) in triangle.iter().zip(expected) {
+                        let [x, y] = mesh.vertices[*idx as usize];
+                        let (cx, cy) = corners[corner];
+                        assert!((x - cx).abs() < 1e-3 && (y - cy).abs() < 1e-3);
+                    }
+
+                    // the triangles are clockwise on the screen, like the corners
+                    let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+                    let cross = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+                    assert!(cross > 0.0);
+                }
+            }
+
+            // every outlined edge is as long as the side of a hex
+            for edge in mesh.outline.unwrap().chunks(2) {
+                let [a, b] = [0, 1].map(|i| mesh.vertices[edge[i] as usize]);
+                let len = (a[0] - b[0]).hypot(a[1] - b[1]);
+                assert!(len > 2.9 && len < 4.1);
+            }
+        }
+    }
+
+    #[test]
+    fn mesh_attributes() {
+        let layout = Layout::<f64>::default();
+        let options = MeshOptions {
+            dedup: true,
+            outline: false,
+        };
+
+        // the corners shared by hexes with different attributes aren't merged
+        let colors = [(0usize, "red"), (1, "red"), (2, "blue")];
+        let mesh = HexMesh::new(colors, &layout, options);
+        assert_eq!(mesh.vertices.len(), 6 + 4 + 6);
+        assert_eq!(mesh.attributes.iter().filter(|&&c| c == "red").count(), 10);
+
+        for (hex, triangles) in mesh.indices.chunks(12).enumerate() {
+            for idx in triangles {
+                assert_eq!(mesh.attributes[*idx as usize], colors[hex].1);
+            }
+        }
+
+        let elevations = [(0u16, 1.5), (1, 1.5), (2, 1.5)];
+        let mesh = HexMesh::new(elevations, &layout, options);
+        assert_eq!(mesh.vertices.len(), 6 + 4 + 3);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn obj_export() {
+        use crate::position::neighboring_positions;
+
+        // Parses the vertices and faces of an OBJ model.
+        fn parse(obj: &str) -> (Vec<[f64; 3]>, Vec<Vec<usize>>) {
+            let (mut vertices, mut faces) = (Vec::new(), Vec::new());
+            for line in obj.lines() {
+                let mut parts = line.split(' ');
+                match parts.next() {
+                    Some("v") => {
+                        let coords = parts.map(|c| c.parse().unwrap()).collect::<Vec<_>>();
+                        vertices.push([coords[0], coords[1], coords[2]]);
+                    }
+                    Some("f") => {
+                        faces.push(parts.map(|i| i.parse::<usize>().unwrap() - 1).collect())
+                    }
+                    _ => {}
+                }
+            }
+            (vertices, faces)
+        }
+
+        let layout = Layout::<f64>::new((2.0, 2.0), (0.0, 0.0), Orientation::Pointy);
+        let export = |heights: &HashMap<Pos, f64>| {
+            let mut obj = Vec::new();
+            write_obj(heights, &layout, &mut obj).unwrap();
+            parse(&String::from_utf8(obj).unwrap())
+        };
+
+        // a single hex has a top and 6 walls
+        let (vertices, faces) = export(&HashMap::from([(0, 1.0)]));
+        assert_eq!((vertices.len(), faces.len()), (6 + 6 * 4, 7));
+
+        // there are walls only where the neighbors are lower, with the missing ones at 0
+        let heights = HashMap::from([(0, 3.0), (1, 3.0), (2, 1.0), (3, 0.0), (4, -1.0)]);
+        let (vertices, faces) = export(&heights);
+        assert_eq!(faces.len(), 5 + 5 + 5 + 4 + 1);
+
+        // the exported model is always the same
+        assert_eq!(export(&heights), (vertices.clone(), faces.clone()));
+
+        // all the faces are counter-clockwise when seen from outside, i.e. their normals point up
+        // for the tops and away from their hex for the walls
+        let positions = {
+            let mut poss = heights.keys().copied().collect::<Vec<_>>();
+            poss.sort_unstable();
+            poss
+        };
+        let mut faces = faces.into_iter();
+        for pos in positions {
+            let (cx, cy) = layout.to_pixel(pos);
+            let walls = neighboring_positions(pos)
+                .into_iter()
+                .filter(|n| heights.get(n).copied().unwrap_or(0.0) < heights[&pos])
+                .count();
+
+            for (i, face) in faces.by_ref().take(1 + walls).enumerate() {
+                let points = face.iter().map(|&v| vertices[v]).collect::<Vec<_>>();
+                let mut normal = [0.0; 3];
+                for (j, p) in points.iter().enumerate() {
+                    let q = points[(j + 1) % points.len()];
+                    normal[0] += (p[1] - q[1]) * (p[2] + q[2]);
+                    normal[1] += (p[2] - q[2]) * (p[0] + q[0]);
+                    normal[2] += (p[0] - q[0]) * (p[1] + q[1]);
+                }
+
+                if i == 0 {
+                    assert!(normal[1] > 0.0);
+                    assert!(points.iter().all(|p| p[1] == heights[&pos]));
+                } else {
+                    let (mx, mz) = (points[0][0] + points[1][0], points[0][2] + points[1][2]);
+                    let outward = (mx / 2.0 - cx) * normal[0] + (mz / 2.0 - cy) * normal[2];
+                    assert!(normal[1].abs() < 1e-9 && outward > 0.0);
+                }
+            }
+        }
+    }
+}
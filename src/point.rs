@@ -1,64 +1,748 @@
-use std::f32::consts::PI;
+use core::f32::consts::PI;
 
-use crate::position::*;
+use num_traits::{Float, FloatConst, NumCast, ToPrimitive};
 
+use crate::{
+    convert::{cube_to_spiral, spiral_to_cube, Cube},
+    error::HexError,
+    position::*,
+};
+
+/// The angle between the subsequent corners of a hex, as seen from its center; see
+/// [`FloatConst::FRAC_PI_3`] for other float types.
 pub const A: f32 = 2.0 * PI / 6.0;
 
-pub fn pos_to_point(pos: Pos, r: f32, window_center: (f32, f32)) -> (f32, f32) {
-    if pos == 0 {
-        return window_center;
+// The distance between the centers of neighboring hexes with a unit circumradius.
+const SQRT_3: f64 = 1.732_050_807_568_877_2;
+
+/// The orientation of the hexes on the screen; the spiral numbering is the same for both of them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Orientation {
+    /// The hexes have a flat top edge and position `1` is directly above the center.
+    #[default]
+    Flat,
+    /// The flat-topped grid rotated by 30° clockwise; the hexes have a pointy top corner and
+    /// position `1` is above and to the right of the center.
+    Pointy,
+}
+
+impl Orientation {
+    // The matrix `[a, b, c, d]` converting axial coordinates to the point `(a * q + b * r,
+    // c * q + d * r)`, for hexes with a unit circumradius and the origin at (0, 0).
+    const fn to_unit_point_matrix(self) -> [f64; 4] {
+        match self {
+            Orientation::Flat => [1.5, 0.0, SQRT_3 / 2.0, SQRT_3],
+            Orientation::Pointy => [SQRT_3 / 2.0, -SQRT_3 / 2.0, 1.5, 1.5],
+        }
+    }
+
+    // The inverse of `to_unit_point_matrix`.
+    const fn to_axial_matrix(self) -> [f64; 4] {
+        match self {
+            Orientation::Flat => [2.0 / 3.0, 0.0, -1.0 / 3.0, SQRT_3 / 3.0],
+            Orientation::Pointy => [SQRT_3 / 3.0, 1.0 / 3.0, -SQRT_3 / 3.0, 1.0 / 3.0],
+        }
+    }
+
+    // Returns the center of the hex with the given axial coordinates, for hexes with a unit
+    // circumradius and the origin at (0, 0).
+    fn axial_to_unit_point<F: Float>(self, q: F, r: F) -> (F, F) {
+        let [a, b, c, d] = self.to_unit_point_matrix().map(float::<F, _>);
+
+        (a * q + b * r, c * q + d * r)
+    }
+
+    // The inverse of `axial_to_unit_point`; returns fractional axial coordinates.
+    fn unit_point_to_axial<F: Float>(self, x: F, y: F) -> (F, F) {
+        let [a, b, c, d] = self.to_axial_matrix().map(float::<F, _>);
+
+        (a * x + b * y, c * x + d * y)
+    }
+
+    // The angle of the first corner of a hex; the following ones are placed clockwise every `A`.
+    fn first_corner_angle<F: Float + FloatConst>(self) -> F {
+        match self {
+            Orientation::Flat => F::zero(),
+            Orientation::Pointy => F::FRAC_PI_6(),
+        }
+    }
+}
+
+/// The placement of the hex grid on the screen; the coordinates can use any float type, e.g.
+/// `f64` for large maps, where `f32` isn't precise enough.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Layout<F = f32> {
+    /// The circumradius of the hexes along the x and y axes.
+    pub size: (F, F),
+    /// The center of the hex at position `0`.
+    pub origin: (F, F),
+    /// The orientation of the hexes.
+    pub orientation: Orientation,
+    /// The clockwise rotation of the whole grid around the origin, in radians.
+    pub rotation: F,
+}
+
+impl<F: Float + FloatConst> Layout<F> {
+    /// Creates a new `Layout` without any rotation.
+    pub fn new(size: (F, F), origin: (F, F), orientation: Orientation) -> Self {
+        Self {
+            size,
+            origin,
+            orientation,
+            rotation: F::zero(),
+        }
+    }
+
+    /// Returns the `Layout` rotated clockwise around the origin by the given angle, in radians.
+    pub fn with_rotation(self, rotation: F) -> Self {
+        Self { rotation, ..self }
+    }
+
+    /// Returns the center of the hex at the given position.
+    pub fn to_pixel<P: PosInt>(&self, pos: P) -> (F, F) {
+        let cube = spiral_to_cube(pos);
+        let (x, y) = self
+            .orientation
+            .axial_to_unit_point(float::<F, _>(cube.q()), float(cube.r()));
+
+        self.unit_to_pixel(x, y)
+    }
+
+    /// Returns the position of the hex containing the given point, or an error if it lies beyond
+    /// `max_ring` (when provided); the boundaries are resolved like in [`point_to_pos`].
+    pub fn from_pixel<P: PosInt>(&self, point: (F, F), max_ring: Option<P>) -> Result<P, HexError> {
+        let (x, y) = self.pixel_to_unit(point.0, point.1)?;
+        let (q, r) = self.orientation.unit_point_to_axial(x, y);
+
+        // The conversion from pixels loses precision in proportion to the magnitude of the
+        // coordinates, measured in hexes, so the points within that error of a boundary are
+        // treated as lying on it.
+        let magnitude = (point.0.abs() + point.1.abs() + self.origin.0.abs() + self.origin.1.abs())
+            / self.size.0.abs().min(self.size.1.abs());
+        let tolerance = F::epsilon() * float(64) * (F::one() + magnitude);
+
+        fractional_axial_to_pos(q, r, tolerance, max_ring)
     }
 
-    let ring = ring(pos);
-    let edge_idx = ring_edge_index(pos);
+    /// Returns the corners of the hex at the given position, clockwise on the screen; for flat
+    /// topped hexes the first one is on the right, and for pointy topped ones, at the bottom right.
+    pub fn corners<P: PosInt>(&self, pos: P) -> [(F, F); 6] {
+        let cube = spiral_to_cube(pos);
+        let (x, y) = self
+            .orientation
+            .axial_to_unit_point(float::<F, _>(cube.q()), float(cube.r()));
+        let first_angle = self.orientation.first_corner_angle::<F>();
 
-    if is_at_ring_tip(pos) {
-        let ring = ring as f32;
+        core::array::from_fn(|i| {
+            let angle = first_angle + F::FRAC_PI_3() * float(i);
+            self.unit_to_pixel(x + angle.cos(), y + angle.sin())
+        })
+    }
 
-        let (xm, ym) = match edge_idx {
-            0 => (0.0, -2.0 * ring),
-            1 => (3.0 * ring, -ring),
-            2 => (3.0 * ring, ring),
-            3 => (0.0, 2.0 * ring),
-            4 => (-3.0 * ring, ring),
-            5 => (-3.0 * ring, -ring),
-            _ => unreachable!(),
-        };
-        let x = (xm * (r * A.cos())) as f32;
-        let y = (ym * (r * A.sin())) as f32;
+    /// Writes the corners of the hexes at the given positions into the buffer, 6 per hex and in
+    /// the same order as [`Layout::corners`].
+    pub fn write_corners<P: PosInt>(
+        &self,
+        poss: impl IntoIterator<Item = P>,
+        buf: &mut impl Extend<(F, F)>,
+    ) {
+        buf.extend(poss.into_iter().flat_map(|pos| self.corners(pos)));
+    }
+
+    /// Returns the smallest and the largest coordinates of the corners of the hexes at the given
+    /// positions, i.e. their bounding box, or `None` if there are no positions.
+    pub fn bounds<P: PosInt>(&self, poss: impl IntoIterator<Item = P>) -> Option<((F, F), (F, F))> {
+        poss.into_iter()
+            .flat_map(|pos| self.corners(pos))
+            .fold(None, |bounds, (x, y)| {
+                let ((min_x, min_y), (max_x, max_y)) = bounds.unwrap_or(((x, y), (x, y)));
+                Some(((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))))
+            })
+    }
 
-        (window_center.0 + x, window_center.1 + y)
+    // Scales, rotates and translates a point from the grid with a unit circumradius.
+    fn unit_to_pixel(&self, x: F, y: F) -> (F, F) {
+        let (x, y) = (x * self.size.0, y * self.size.1);
+
+        if self.rotation.is_zero() {
+            return (self.origin.0 + x, self.origin.1 + y);
+        }
+
+        let (sin, cos) = self.rotation.sin_cos();
+
+        // Since the y axis points down, the standard rotation is clockwise on the screen.
+        (
+            self.origin.0 + x * cos - y * sin,
+            self.origin.1 + x * sin + y * cos,
+        )
+    }
+
+    // The inverse of `unit_to_pixel`; fails if the point or the layout aren't finite, or if the
+    // layout has a zero size.
+    fn pixel_to_unit(&self, x: F, y: F) -> Result<(F, F), HexError> {
+        let Layout {
+            size,
+            origin,
+            rotation,
+            ..
+        } = *self;
+
+        if ![x, y, size.0, size.1, origin.0, origin.1, rotation]
+            .into_iter()
+            .all(F::is_finite)
+            || size.0.is_zero()
+            || size.1.is_zero()
+        {
+            return Err(HexError::InvalidPoint);
+        }
+
+        let (mut x, mut y) = (x - origin.0, y - origin.1);
+
+        if !rotation.is_zero() {
+            let (sin, cos) = rotation.sin_cos();
+            (x, y) = (x * cos + y * sin, y * cos - x * sin);
+        }
+
+        Ok((x / size.0, y / size.1))
+    }
+}
+
+impl<F: Float + FloatConst> Default for Layout<F> {
+    fn default() -> Self {
+        Self::new(
+            (F::one(), F::one()),
+            (F::zero(), F::zero()),
+            Orientation::Flat,
+        )
+    }
+}
+
+/// Returns the center of the hex at the given position in window coordinates, for hexes with
+/// the circumradius `r` and the chosen orientation. It's calculated directly from the position's
+/// cube coordinates.
+pub fn pos_to_point<P: PosInt, F: Float + FloatConst>(
+    pos: P,
+    r: F,
+    window_center: (F, F),
+    orientation: Orientation,
+) -> (F, F) {
+    let layout = Layout::new((r, r), window_center, orientation);
+
+    layout.to_pixel(pos)
+}
+
+/// Returns the corners of the hex at the given position in window coordinates, for hexes with
+/// the circumradius `r` and the chosen orientation, like in [`pos_to_point`]; they are ordered
+/// like in [`Layout::corners`].
+pub fn hex_corners<P: PosInt, F: Float + FloatConst>(
+    pos: P,
+    r: F,
+    window_center: (F, F),
+    orientation: Orientation,
+) -> [(F, F); 6] {
+    let layout = Layout::new((r, r), window_center, orientation);
+
+    layout.corners(pos)
+}
+
+/// Writes the corners of the hexes at the given positions into the buffer, 6 per hex and in the
+/// same order as in [`hex_corners`].
+pub fn write_hex_corners<P: PosInt, F: Float + FloatConst>(
+    poss: impl IntoIterator<Item = P>,
+    r: F,
+    window_center: (F, F),
+    orientation: Orientation,
+    buf: &mut impl Extend<(F, F)>,
+) {
+    let layout = Layout::new((r, r), window_center, orientation);
+
+    layout.write_corners(poss, buf);
+}
+
+/// Returns the position of the hex containing the given point, for hexes with the circumradius
+/// `r` and the chosen orientation, or an error if it lies beyond `max_ring` (when provided).
+///
+/// The points on the boundaries between hexes, up to the precision of the conversion, always
+/// belong to the same hex: each one owns its north, north-east and north-west edges, and the 2
+/// corners at the ends of its north edge (in the flat-topped grid; for pointy-topped hexes the
+/// directions are rotated by 30 degrees clockwise).
+pub fn point_to_pos<P: PosInt, F: Float + FloatConst>(
+    point_x: F,
+    point_y: F,
+    window_center_x: F,
+    window_center_y: F,
+    r: F,
+    orientation: Orientation,
+    max_ring: Option<P>,
+) -> Result<P, HexError> {
+    let layout = Layout::new((r, r), (window_center_x, window_center_y), orientation);
+
+    layout.from_pixel((point_x, point_y), max_ring)
+}
+
+// Returns the position of the hex containing the point with the given fractional axial
+// coordinates, or an error if it lies beyond `max_ring` (when provided). The points within
+// `tolerance` of a boundary belong to the hex with the largest `r` component among the adjacent
+// ones, and then with the largest `q` one.
+fn fractional_axial_to_pos<P: PosInt, F: Float>(
+    q: F,
+    r: F,
+    tolerance: F,
+    max_ring: Option<P>,
+) -> Result<P, HexError> {
+    let s = -q - r;
+
+    let (mut rq, mut rr, mut rs) = (round_half_up(q), round_half_up(r), round_half_up(s));
+    let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+
+    // The component with the largest rounding error is the least reliable one, so it gets
+    // recalculated from the other two; ties are always broken in the same order.
+    if dq > dr && dq > ds {
+        rq = -rr - rs;
+    } else if dr > ds {
+        rr = -rq - rs;
     } else {
-        let ring_offset = ring_offset(ring);
-        let ring_pos = pos - ring_offset;
-        let tip_offset = ring_pos - edge_idx * ring;
-        let tip_pos = ring_offset + edge_idx * ring;
-        let ring = tip_offset as f32;
-
-        let tip_point = pos_to_point(tip_pos, r, window_center);
-
-        let (xm, ym) = match (edge_idx + 2) % 6 {
-            0 => (0.0, -2.0 * ring),
-            1 => (3.0 * ring, -ring),
-            2 => (3.0 * ring, ring),
-            3 => (0.0, 2.0 * ring),
-            4 => (-3.0 * ring, ring),
-            5 => (-3.0 * ring, -ring),
-            _ => unreachable!(),
-        };
-        let x = (xm * (r * A.cos())) as f32;
-        let y = (ym * (r * A.sin())) as f32;
-
-        (tip_point.0 + x, tip_point.1 + y)
+        rs = -rq - rr;
+    }
+
+    if ![rq, rr, rs]
+        .into_iter()
+        .all(|c| c.is_finite() && c.abs() <= float(i64::MAX / 2))
+    {
+        return Err(HexError::Overflow);
     }
+
+    let rounded = Cube::new_unchecked(
+        rq.to_i64().unwrap(),
+        rr.to_i64().unwrap(),
+        rs.to_i64().unwrap(),
+    );
+
+    // The point can only be as close to the neighbors of the rounded hex as to the hex itself;
+    // with `f` being its offset from the rounded center, it's closer to the neighbor in the
+    // direction `d` iff `f · d > 1`, so this margin decides which of the hexes are tied.
+    let (fq, fr, fs) = (q - rq, r - rr, s - rs);
+    let margin = |dir: Direction| {
+        let d = Cube::from(dir);
+        fq * float(d.q()) + fr * float(d.r()) + fs * float(d.s()) - F::one()
+    };
+    let best = Direction::iter().map(margin).fold(F::zero(), F::max);
+
+    let cube = Direction::iter()
+        .filter(|&dir| margin(dir) >= best - tolerance)
+        .map(|dir| rounded + Cube::from(dir))
+        .chain((best <= tolerance).then_some(rounded))
+        .max_by_key(|cube| (cube.r(), cube.q()))
+        .unwrap();
+
+    if let Some(max_ring) = max_ring.and_then(|max_ring| max_ring.to_u64()) {
+        let ring = cube.abs_largest();
+
+        if ring > max_ring {
+            return Err(HexError::RingOutOfBounds {
+                ring: ring.into(),
+                max_ring: max_ring.into(),
+            });
+        }
+    }
+
+    cube_to_spiral(cube)
 }
 
-pub fn point_to_pos(
-    point_x: f32,
-    point_y: f32,
-    window_center_x: f32,
-    window_center_y: f32,
-    r: f32,
-) -> Option<Pos> {
-    todo!();
+// Unlike `Float::round`, this rounds halves in the same direction regardless of sign, which keeps
+// the rounded hex, and thus the candidates for the ties, identical across the whole map.
+fn round_half_up<F: Float>(x: F) -> F {
+    (x + float(0.5)).floor()
+}
+
+// Converts a number to the chosen float type; all the ones used in this module are representable
+// in any of them, at worst with a loss of precision.
+fn float<F: Float, T: ToPrimitive>(x: T) -> F {
+    <F as NumCast>::from(x).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQRT_3: f32 = 1.732_050_8;
+
+    // Returns `true` if the given points are equal, save for the loss of precision.
+    fn close((x1, y1): (f32, f32), (x2, y2): (f32, f32)) -> bool {
+        let tolerance = 1e-6 * x1.abs().max(y1.abs()).max(1e3);
+        (x1 - x2).abs() < tolerance && (y1 - y2).abs() < tolerance
+    }
+
+    #[test]
+    fn pos_to_point_values() {
+        let point = |pos: Pos| pos_to_point(pos, 2.0, (100.0, 50.0), Orientation::Flat);
+
+        assert_eq!(point(0), (100.0, 50.0));
+        assert!(close(point(1), (100.0, 50.0 - 2.0 * SQRT_3)));
+        assert!(close(point(2), (103.0, 50.0 - SQRT_3)));
+        assert!(close(point(8), (103.0, 50.0 - 3.0 * SQRT_3)));
+        assert!(close(point(12), (103.0, 50.0 + 3.0 * SQRT_3)));
+
+        // the tips of every ring lie on straight lines going through the center
+        for (ring, pos) in DirectionalNeighborIter::new(0usize, Direction::N)
+            .take(1000)
+            .enumerate()
+        {
+            let ring = (ring + 1) as f32;
+            assert_eq!(pos, ring_offset(ring as usize));
+            assert!(close(point(pos), (100.0, 50.0 - 2.0 * SQRT_3 * ring)));
+            assert!(close(
+                point(pos + 3 * ring as usize),
+                (100.0, 50.0 + 2.0 * SQRT_3 * ring)
+            ));
+        }
+    }
+
+    #[test]
+    fn point_to_pos_round_trip() {
+        for orientation in [Orientation::Flat, Orientation::Pointy] {
+            for r in [1.0f32, 7.5, 32.0] {
+                for center in [(0.0, 0.0), (400.0, 300.0), (-12.5, 1000.0)] {
+                    for pos in 0..ring_offset(12usize) {
+                        let (x, y) = pos_to_point(pos, r, center, orientation);
+                        assert_eq!(
+                            point_to_pos(x, y, center.0, center.1, r, orientation, None),
+                            Ok(pos)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn point_to_pos_inside_hexes() {
+        let r = 10.0;
+
+        for (orientation, first_corner) in
+            [(Orientation::Flat, 0.0), (Orientation::Pointy, A / 2.0)]
+        {
+            for pos in 0..ring_offset(6usize) {
+                let (x, y) = pos_to_point(pos, r, (0.0, 0.0), orientation);
+
+                // points slightly inside each of the corners still belong to the same hex
+                for i in 0..6 {
+                    let angle = first_corner + A * i as f32;
+                    let (cx, cy) = (x + 0.9 * r * angle.cos(), y + 0.9 * r * angle.sin());
+                    assert_eq!(
+                        point_to_pos(cx, cy, 0.0, 0.0, r, orientation, None),
+                        Ok(pos)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn point_to_pos_boundaries() {
+        use Direction::*;
+
+        // the exact midpoints of the edges belong to the hex with the edge on its N, NE or NW side
+        for pos in 0..ring_offset(8usize) {
+            let cube = spiral_to_cube(pos);
+
+            for dir in Direction::iter() {
+                let neighbor = neighboring_positions(pos)[dir.index()];
+                let other = spiral_to_cube(neighbor);
+                let (q, r) = (
+                    (cube.q() + other.q()) as f64 / 2.0,
+                    (cube.r() + other.r()) as f64 / 2.0,
+                );
+                let owner = if matches!(dir, N | NE | NW) {
+                    pos
+                } else {
+                    neighbor
+                };
+
+                assert_eq!(fractional_axial_to_pos(q, r, 0.0, None::<Pos>), Ok(owner));
+            }
+        }
+
+        // which also holds for the ones calculated in pixels, in both orientations, and the hexes
+        // own the last 2 corners, at the ends of their N edges
+        for orientation in [Orientation::Flat, Orientation::Pointy] {
+            let layouts = [
+                Layout::new((1.0f32, 1.0), (0.0, 0.0), orientation),
+                Layout::new((10.0, 6.0), (400.0, 300.0), orientation).with_rotation(0.7),
+                Layout::new((32.0, 32.0), (-960.0, 540.0), orientation),
+            ];
+
+            for layout in layouts {
+                for pos in 0..ring_offset(6usize) {
+                    let (x, y) = layout.to_pixel(pos);
+                    let neighbors = neighboring_positions(pos);
+
+                    for dir in Direction::iter() {
+                        let neighbor = neighbors[dir.index()];
+                        let (nx, ny) = layout.to_pixel(neighbor);
+                        let midpoint = ((x + nx) / 2.0, (y + ny) / 2.0);
+                        let owner = if matches!(dir, N | NE | NW) {
+                            pos
+                        } else {
+                            neighbor
+                        };
+
+                        assert_eq!(layout.from_pixel(midpoint, None), Ok(owner));
+                    }
+
+                    for (i, corner) in layout.corners(pos).into_iter().enumerate() {
+                        let owner = layout.from_pixel(corner, None).unwrap();
+
+                        assert_eq!(owner == pos, i >= 4);
+                        assert!(owner == pos || neighbors.contains(&owner));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn point_to_pos_max_ring() {
+        let r = 5.0;
+
+        for pos in 0..ring_offset(4usize) {
+            let (x, y) = pos_to_point(pos, r, (0.0, 0.0), Orientation::Pointy);
+            let expected = if ring(pos) <= 2 {
+                Ok(pos)
+            } else {
+                Err(HexError::RingOutOfBounds {
+                    ring: ring(pos) as u128,
+                    max_ring: 2,
+                })
+            };
+            assert_eq!(
+                point_to_pos(x, y, 0.0, 0.0, r, Orientation::Pointy, Some(2)),
+                expected
+            );
+        }
+
+        assert_eq!(
+            point_to_pos::<Pos, _>(f32::NAN, 0.0, 0.0, 0.0, r, Orientation::Flat, None),
+            Err(HexError::InvalidPoint)
+        );
+        assert_eq!(
+            point_to_pos::<Pos, _>(f32::MAX, 0.0, 0.0, 0.0, r, Orientation::Flat, None),
+            Err(HexError::Overflow)
+        );
+
+        // the layout can't be degenerate either
+        for r in [0.0, -0.0, f32::INFINITY, f32::NAN] {
+            assert_eq!(
+                point_to_pos::<Pos, _>(1.0, 1.0, 0.0, 0.0, r, Orientation::Flat, None),
+                Err(HexError::InvalidPoint)
+            );
+        }
+    }
+
+    #[test]
+    fn orientations() {
+        let r = 2.0;
+
+        let pointy = |pos: Pos| pos_to_point(pos, r, (10.0, 10.0), Orientation::Pointy);
+
+        assert_eq!(pointy(0), (10.0, 10.0));
+        assert!(close(pointy(1), (10.0 + SQRT_3 / 2.0 * r, 10.0 - 1.5 * r)));
+        assert!(close(pointy(2), (10.0 + SQRT_3 * r, 10.0)));
+        assert!(close(pointy(4), (10.0 - SQRT_3 / 2.0 * r, 10.0 + 1.5 * r)));
+        assert!(close(pointy(7), (10.0 + SQRT_3 * r, 10.0 - 3.0 * r)));
+
+        // the distance between neighbors is the same regardless of the orientation
+        for pos in 0..ring_offset(4usize) {
+            for neighbor in neighboring_positions(pos) {
+                let (x1, y1) = pointy(pos);
+                let (x2, y2) = pointy(neighbor);
+                assert!(((x1 - x2).hypot(y1 - y2) - SQRT_3 * r).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn layout_pixels() {
+        for orientation in [Orientation::Flat, Orientation::Pointy] {
+            let layout = Layout::new((7.5, 7.5), (400.0, 300.0), orientation);
+
+            for pos in 0..ring_offset(12usize) {
+                let point = layout.to_pixel(pos);
+                assert!(close(
+                    point,
+                    pos_to_point(pos, 7.5, (400.0, 300.0), orientation)
+                ));
+                assert_eq!(layout.from_pixel(point, None), Ok(pos));
+            }
+
+            let layouts = [
+                Layout::new((10.0, 5.0), (-20.0, 35.0), orientation),
+                Layout::new((3.0, 4.0), (0.0, 0.0), orientation).with_rotation(A / 4.0),
+                Layout::new((8.0, 8.0), (100.0, 100.0), orientation).with_rotation(-2.0),
+            ];
+
+            for layout in layouts {
+                for pos in 0..ring_offset(8usize) {
+                    let (x, y) = layout.to_pixel(pos);
+                    assert_eq!(layout.from_pixel((x, y), None), Ok(pos));
+
+                    // points slightly inside each of the corners still belong to the same hex
+                    for (cx, cy) in layout.corners(pos) {
+                        let inside = (x + 0.9 * (cx - x), y + 0.9 * (cy - y));
+                        assert_eq!(layout.from_pixel(inside, None), Ok(pos));
+                    }
+                }
+            }
+        }
+
+        assert_eq!(
+            Layout::default().from_pixel::<Pos>((f32::INFINITY, 0.0), None),
+            Err(HexError::InvalidPoint)
+        );
+        assert_eq!(
+            Layout::default()
+                .with_rotation(f32::NAN)
+                .from_pixel::<Pos>((0.0, 0.0), None),
+            Err(HexError::InvalidPoint)
+        );
+        assert_eq!(
+            Layout::new((1.0, 0.0), (0.0, 0.0), Orientation::Pointy)
+                .from_pixel::<Pos>((0.0, 0.0), None),
+            Err(HexError::InvalidPoint)
+        );
+    }
+
+    #[test]
+    fn layout_corners() {
+        let h = SQRT_3 / 2.0;
+
+        let flat = Layout::new((2.0, 2.0), (1.0, 1.0), Orientation::Flat);
+        let expected = [
+            (3.0, 1.0),
+            (2.0, 1.0 + 2.0 * h),
+            (0.0, 1.0 + 2.0 * h),
+            (-1.0, 1.0),
+            (0.0, 1.0 - 2.0 * h),
+            (2.0, 1.0 - 2.0 * h),
+        ];
+        for (corner, expected) in flat.corners(0usize).into_iter().zip(expected) {
+            assert!(close(corner, expected));
+        }
+
+        let pointy = Layout::new((1.0, 1.0), (0.0, 0.0), Orientation::Pointy);
+        assert!(close(pointy.corners(0usize)[0], (h, 0.5)));
+        assert!(close(pointy.corners(0usize)[4], (0.0, -1.0)));
+
+        // neighboring hexes share 2 corners
+        for layout in [flat, pointy.with_rotation(1.0)] {
+            for pos in 0..ring_offset(5usize) {
+                for neighbor in neighboring_positions(pos) {
+                    let shared = layout
+                        .corners(pos)
+                        .into_iter()
+                        .filter(|&c1| layout.corners(neighbor).into_iter().any(|c2| close(c1, c2)))
+                        .count();
+                    assert_eq!(shared, 2);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn layout_bounds() {
+        let h = SQRT_3 / 2.0;
+
+        let layout = Layout::new((1.0, 2.0), (10.0, 10.0), Orientation::Flat);
+        assert_eq!(layout.bounds(core::iter::empty::<Pos>()), None);
+
+        let (min, max) = layout.bounds([0usize]).unwrap();
+        assert!(close(min, (9.0, 10.0 - 2.0 * h)) && close(max, (11.0, 10.0 + 2.0 * h)));
+
+        // the first ring and the center span 2.5 hexes horizontally and 3 vertically
+        let (min, max) = layout.bounds(0..7usize).unwrap();
+        assert!(close(min, (7.5, 10.0 - 6.0 * h)) && close(max, (12.5, 10.0 + 6.0 * h)));
+
+        // a half turn doesn't change the bounds of a whole ring
+        let rotated = layout.with_rotation(PI);
+        let (rmin, rmax) = rotated.bounds(0..7usize).unwrap();
+        assert!(close(min, rmin) && close(max, rmax));
+    }
+
+    #[test]
+    fn f64_precision() {
+        let r = 32.0;
+        let sqrt_3 = 3.0f64.sqrt();
+        let layout = Layout::<f64>::new((r, r), (960.0, 540.0), Orientation::Pointy);
+
+        // far rings lose too much precision with f32 for the hexes to be told apart
+        for ring in [100_000usize, 3_000_000, 20_000_000] {
+            for pos in (ring_offset(ring)..ring_offset(ring + 1)).step_by(ring / 5 + 1) {
+                let (x, y) = layout.to_pixel(pos);
+                assert_eq!(layout.from_pixel((x, y), None), Ok(pos));
+                assert_eq!(
+                    point_to_pos(x, y, 960.0, 540.0, r, Orientation::Pointy, None),
+                    Ok(pos)
+                );
+
+                let (px, py) = pos_to_point(pos, r, (960.0, 540.0), Orientation::Pointy);
+                assert!((px - x).abs() < 1e-3 && (py - y).abs() < 1e-3);
+
+                for neighbor in neighboring_positions(pos) {
+                    let (nx, ny) = layout.to_pixel(neighbor);
+                    assert!(((nx - x).hypot(ny - y) - sqrt_3 * r).abs() < 1e-6);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hex_corner_values() {
+        for orientation in [Orientation::Flat, Orientation::Pointy] {
+            for pos in 0..ring_offset(6usize) {
+                let (x, y) = pos_to_point(pos, 8.0, (40.0, 30.0), orientation);
+                let corners = hex_corners(pos, 8.0, (40.0, 30.0), orientation);
+
+                // the corners are r away from the center and the side length is r as well
+                for (i, (cx, cy)) in corners.into_iter().enumerate() {
+                    let (nx, ny) = corners[(i + 1) % 6];
+                    assert!(((cx - x).hypot(cy - y) - 8.0).abs() < 1e-3);
+                    assert!(((cx - nx).hypot(cy - ny) - 8.0).abs() < 1e-3);
+                }
+
+                // the corners go clockwise on the screen
+                let (ax, ay) = corners[0];
+                let (bx, by) = corners[1];
+                assert!((ax - x) * (by - y) - (ay - y) * (bx - x) > 0.0);
+            }
+        }
+
+        assert!(close(
+            hex_corners(0usize, 1.0, (0.0, 0.0), Orientation::Flat)[0],
+            (1.0, 0.0)
+        ));
+        assert!(close(
+            hex_corners(0usize, 1.0, (0.0, 0.0), Orientation::Pointy)[4],
+            (0.0, -1.0)
+        ));
+    }
+
+    #[test]
+    fn write_corners_to_buffer() {
+        let layout = Layout::new((5.0, 5.0), (1.0, 2.0), Orientation::Pointy);
+        let poss = [0usize, 7, 3, 60];
+
+        let mut buf = vec![(0.0, 0.0)];
+        layout.write_corners(poss, &mut buf);
+        assert_eq!(buf.len(), 1 + 6 * poss.len());
+
+        for (pos, corners) in poss.into_iter().zip(buf[1..].chunks(6)) {
+            assert_eq!(corners, layout.corners(pos));
+        }
+
+        let mut buf2 = Vec::new();
+        write_hex_corners(poss, 5.0, (1.0, 2.0), Orientation::Pointy, &mut buf2);
+        assert_eq!(buf2, buf[1..]);
+    }
 }
@@ -1,21 +1,67 @@
 //! While most hex-grid-based 2D games use multiple coordinates, **hex-spiral** uses a single-coordinate spiral,
 //! where the central hex has the position `0`, and further hexes are placed within theoretical hexagonal rings
-//! that surround it. The hexes are flat-topped and every ring is indexed starting with the hex on the top edge
-//! of the previous ring and with further positions growing clockwise.
+//! that surround it. Every ring is indexed starting with the hex on the top edge of the previous ring and with
+//! further positions growing clockwise; this describes flat-topped hexes, while pointy-topped ones share the same
+//! numbering, only rotated by 30° clockwise.
 
+use core::{fmt::Debug, hash::Hash};
+
+#[cfg(feature = "std")]
 use itertools::Itertools;
+use num_traits::{PrimInt, Unsigned};
+
+#[cfg(any(feature = "std", feature = "libm"))]
+use num_traits::{Float, FloatConst};
+
+#[cfg(any(feature = "std", feature = "libm"))]
+use crate::point::{pos_to_point, Orientation};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::convert::cube_to_spiral;
+use crate::{
+    convert::{checked_spiral_to_cube, spiral_to_cube, Cube},
+    error::HexError,
+};
 
 pub type Pos = usize;
+#[deprecated(note = "ring indices have the same type as the positions, e.g. `Pos`")]
 pub type RingIdx = usize;
 
+/// An unsigned integer type that can be used to represent positions; [`Pos`] is the default one.
+///
+/// The functions that calculate positions panic if their result doesn't fit in the chosen type,
+/// regardless of the build profile; their `checked_*` counterparts return `None` instead.
+pub trait PosInt: PrimInt + Unsigned + Hash + Debug + From<u8> {
+    /// Returns the square root of the number, rounded down.
+    fn isqrt(self) -> Self;
+}
+
+macro_rules! impl_pos_int {
+    ($($t:ty),*) => {
+        $(
+            impl PosInt for $t {
+                fn isqrt(self) -> Self {
+                    <$t>::isqrt(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_pos_int!(u8, u16, u32, u64, u128, usize);
+
+// The positions are widened, so that the groups can be found for all the position types.
+#[cfg(feature = "std")]
 crepe::crepe! {
     @input
-    struct Position(Pos);
+    struct Position(u128);
 
-    struct Neighbors(Pos, Pos);
+    struct Neighbors(u128, u128);
 
     @output
-    struct Grouped(Pos, Pos);
+    struct Grouped(u128, u128);
 
     Neighbors(p1, p2) <- Position(p1), Position(p2), (are_neighbors(p1, p2));
     Grouped(p1, p2) <- Neighbors(p1, p2);
@@ -23,152 +69,306 @@ crepe::crepe! {
 }
 
 /// The starting position of hexes within the ring with the given index.
-pub fn ring_offset(ring: RingIdx) -> Pos {
-    if ring == 0 {
-        0
+pub fn ring_offset<P: PosInt>(ring: P) -> P {
+    checked_ring_offset(ring).expect("ring offset overflow")
+}
+
+/// The starting position of hexes within the ring with the given index, or `None` if it can't be
+/// represented as a `P`.
+pub fn checked_ring_offset<P: PosInt>(ring: P) -> Option<P> {
+    if ring.is_zero() {
+        Some(P::zero())
     } else {
-        3 * (ring - 1) * ring + 1
+        (ring - P::one())
+            .checked_mul(&ring)?
+            .checked_mul(&3.into())?
+            .checked_add(&P::one())
     }
 }
 
-/// The index of the ring for the given position.
-pub fn ring(pos: Pos) -> RingIdx {
-    (0..)
-        .map(ring_offset)
-        .position(|offset| pos < offset)
-        .unwrap()
-        - 1
+/// The index of the ring for the given position. It can't panic, so it has no checked variant.
+pub fn ring<P: PosInt>(pos: P) -> P {
+    if pos.is_zero() {
+        return P::zero();
+    }
+
+    // Ring `k > 0` starts at `3k(k - 1) + 1`, so it's the largest `k` for which
+    // `k(k - 1) <= (pos - 1) / 3`; it's either `isqrt((pos - 1) / 3)` or the next integer.
+    // Dividing first keeps the calculation free of overflow for every `P`.
+    let m = (pos - P::one()) / 3.into();
+    let root = m.isqrt();
+
+    if root * root + root <= m {
+        root + P::one()
+    } else {
+        root
+    }
 }
 
-/// Returns `true` if the given position is at one of the tips of a ring.
-pub fn is_at_ring_tip(pos: Pos) -> bool {
+/// Returns `true` if the given position is at one of the tips of a ring. It can't panic, so it
+/// has no checked variant.
+pub fn is_at_ring_tip<P: PosInt>(pos: P) -> bool {
     let ring = ring(pos);
-    let ring_offset = ring_offset(ring);
 
-    (0..6)
-        .map(|n| ring_offset + n * ring)
-        .any(|ring_tip| pos == ring_tip)
+    ring.is_zero() || ((pos - ring_offset(ring)) % ring).is_zero()
 }
 
 /// Returns the index of the edge of the ring the given position belongs to.
-pub fn ring_edge_index(pos: Pos) -> usize {
+///
+/// # Panics
+///
+/// Panics for the central position, which doesn't belong to any edge; see
+/// [`checked_ring_edge_index`].
+pub fn ring_edge_index<P: PosInt>(pos: P) -> usize {
     let ring = ring(pos);
-    (pos - ring_offset(ring)) / ring
+    ((pos - ring_offset(ring)) / ring).to_usize().unwrap()
+}
+
+/// Returns the index of the edge of the ring the given position belongs to, or `None` for the
+/// central position, which doesn't belong to any edge.
+pub fn checked_ring_edge_index<P: PosInt>(pos: P) -> Option<usize> {
+    if pos.is_zero() {
+        None
+    } else {
+        Some(ring_edge_index(pos))
+    }
 }
 
-/// An iterator returning subsequent neighboring positions in the given direction.
-/// The top direction is `0`, and it increases up to `5` clockwise.
-pub struct DirectionalNeighborIter {
-    curr_pos: Pos,
-    dir: usize,
+/// One of the 6 directions on the (flat-topped) grid, ordered clockwise starting from the top,
+/// just like the neighbors returned by [`neighboring_positions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    N,
+    NE,
+    SE,
+    S,
+    SW,
+    NW,
 }
 
-impl DirectionalNeighborIter {
+impl Direction {
+    /// All the directions, in clockwise order.
+    pub const ALL: [Direction; 6] = [
+        Direction::N,
+        Direction::NE,
+        Direction::SE,
+        Direction::S,
+        Direction::SW,
+        Direction::NW,
+    ];
+
+    /// Returns an iterator over all the directions, in clockwise order.
+    pub fn iter() -> impl Iterator<Item = Direction> {
+        Self::ALL.into_iter()
+    }
+
+    /// Returns the index of the neighbor in this direction in the array returned by
+    /// [`neighboring_positions`].
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the next direction clockwise.
+    pub const fn rotate_cw(self) -> Self {
+        Self::ALL[(self as usize + 1) % 6]
+    }
+
+    /// Returns the next direction counter-clockwise.
+    pub const fn rotate_ccw(self) -> Self {
+        Self::ALL[(self as usize + 5) % 6]
+    }
+
+    /// Returns the opposite direction.
+    pub const fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 3) % 6]
+    }
+}
+
+impl From<Direction> for usize {
+    fn from(dir: Direction) -> Self {
+        dir.index()
+    }
+}
+
+impl TryFrom<usize> for Direction {
+    type Error = HexError;
+
+    fn try_from(idx: usize) -> Result<Self, Self::Error> {
+        Self::ALL
+            .get(idx)
+            .copied()
+            .ok_or(HexError::InvalidDirection(idx))
+    }
+}
+
+/// An iterator returning subsequent neighboring positions in the given direction. It ends
+/// if the next position can't be represented as a `P`.
+pub struct DirectionalNeighborIter<P = Pos> {
+    curr_pos: P,
+    dir: Direction,
+}
+
+impl<P: PosInt> DirectionalNeighborIter<P> {
     /// Create a new `DirectionalNeighborIter` starting at the given position
     /// and progressing in the chosen direction.
-    pub fn new(pos: Pos, dir: usize) -> Self {
-        assert!(dir <= 5);
+    pub fn new(pos: P, dir: Direction) -> Self {
         Self { curr_pos: pos, dir }
     }
 
     /// Returns the position the `DirectionalNeighborIter` is currently at.
-    pub fn curr_pos(&self) -> Pos {
+    pub fn curr_pos(&self) -> P {
         self.curr_pos
     }
 }
 
-impl Iterator for DirectionalNeighborIter {
-    type Item = Pos;
+impl<P: PosInt> Iterator for DirectionalNeighborIter<P> {
+    type Item = P;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let next = neighboring_positions(self.curr_pos)[self.dir];
+        let next = partial_neighboring_positions(self.curr_pos)[self.dir.index()]?;
         self.curr_pos = next;
         Some(next)
     }
 }
 
-fn ring_neighboring_positions(pos: Pos) -> [Pos; 2] {
-    assert!(pos != 0);
+// Returns the position at the given index within the given ring, wrapping around it, or `None`
+// if it can't be represented as a `P`.
+fn position_in_ring<P: PosInt>(ring: P, idx: P) -> Option<P> {
+    let idx = if ring.is_zero() {
+        idx
+    } else {
+        idx % (ring * 6.into())
+    };
 
+    checked_ring_offset(ring)?.checked_add(&idx)
+}
+
+// Returns the 2 neighbors of the given position within its ring, counter-clockwise and clockwise,
+// or `None` in place of the one that can't be represented as a `P`.
+fn ring_neighboring_positions<P: PosInt>(pos: P) -> [Option<P>; 2] {
+    assert!(!pos.is_zero());
+
+    let one = P::one();
     let ring = ring(pos);
+    let idx = pos - ring_offset(ring);
 
-    if pos == ring_offset(ring) {
-        [ring_offset(ring + 1) - 1, pos + 1]
-    } else if pos == ring_offset(ring + 1) - 1 {
-        [pos - 1, ring_offset(ring)]
-    } else {
-        [pos - 1, pos + 1]
-    }
+    [
+        position_in_ring(ring, idx + ring * 6.into() - one),
+        position_in_ring(ring, idx + one),
+    ]
 }
 
 /// Returns the 6 neighbors of the given position, always in the same clockwise order.
-pub fn neighboring_positions(pos: Pos) -> [Pos; 6] {
+pub fn neighboring_positions<P: PosInt>(pos: P) -> [P; 6] {
+    checked_neighboring_positions(pos).expect("neighboring position overflow")
+}
+
+/// Returns the 6 neighbors of the given position, always in the same clockwise order, or `None`
+/// if any of them can't be represented as a `P`.
+pub fn checked_neighboring_positions<P: PosInt>(pos: P) -> Option<[P; 6]> {
+    let poss = partial_neighboring_positions(pos);
+
+    poss.iter()
+        .all(Option::is_some)
+        .then(|| poss.map(Option::unwrap))
+}
+
+// Returns the 6 neighbors of the given position, always in the same clockwise order, with `None`
+// in place of the ones that can't be represented as a `P`. Every neighbor is calculated as an
+// index within its ring, so that it only overflows if the neighbor itself doesn't fit.
+fn partial_neighboring_positions<P: PosInt>(pos: P) -> [Option<P>; 6] {
+    let one = P::one();
     let ring = ring(pos);
 
-    match ring {
-        0 => [1, 2, 3, 4, 5, 6],
-        _ => {
-            let edge_index = ring_edge_index(pos);
-
-            let mut poss = if is_at_ring_tip(pos) {
-                // 1 neighbor from the lower ring, 3 from the upper ring, 2 from the same ring
-                let lower_neighbor = ring_offset(ring - 1) + (ring - 1) * edge_index;
-                let ring_neighbors = ring_neighboring_positions(pos);
-                let upper_tip_neighbor = if pos == ring_offset(ring + 1) - 1 {
-                    ring_offset(ring + 2) - 2
-                } else {
-                    ring_offset(ring + 1) + (ring + 1) * edge_index
-                };
-                let upper_tip_neighbors = ring_neighboring_positions(upper_tip_neighbor);
-
-                [
-                    upper_tip_neighbor,
-                    upper_tip_neighbors[1],
-                    ring_neighbors[1],
-                    lower_neighbor,
-                    ring_neighbors[0],
-                    upper_tip_neighbors[0],
-                ]
-            } else {
-                // 2 neighbors from the lower ring, the upper ring, and the same ring
-                let ring_pos = pos - ring_offset(ring);
-                let tip_offset = ring_pos - (edge_index * ring);
-                let (lower_neighbor1, lower_neighbor2) = if pos == ring_offset(ring + 1) - 1 {
-                    (ring_offset(ring) - 1, ring_offset(ring - 1))
-                } else {
-                    let lower_neighbor1 =
-                        ring_offset(ring - 1) + (edge_index * (ring - 1)) + tip_offset - 1;
-                    (lower_neighbor1, lower_neighbor1 + 1)
-                };
-                let ring_neighbors = ring_neighboring_positions(pos);
-                let upper_neighbor1 =
-                    ring_offset(ring + 1) + (edge_index * (ring + 1)) + tip_offset;
-                let upper_neighbor2 = upper_neighbor1 + 1;
-
-                [
-                    upper_neighbor1,
-                    upper_neighbor2,
-                    ring_neighbors[1],
-                    lower_neighbor2,
-                    lower_neighbor1,
-                    ring_neighbors[0],
-                ]
-            };
-
-            poss.rotate_right(edge_index);
-
-            poss
-        }
+    if ring.is_zero() {
+        return [1u8, 2, 3, 4, 5, 6].map(|pos| Some(pos.into()));
     }
+
+    let (lower_ring, upper_ring) = (ring - one, ring + one);
+    let edge_index = ring_edge_index(pos);
+    let edge: P = (edge_index as u8).into();
+    let tip_offset = pos - ring_offset(ring) - edge * ring;
+    let ring_neighbors = ring_neighboring_positions(pos);
+
+    let mut poss = if tip_offset.is_zero() {
+        // 1 neighbor from the lower ring, 3 from the upper ring, 2 from the same ring
+        let upper_tip = edge * upper_ring;
+
+        [
+            position_in_ring(upper_ring, upper_tip),
+            position_in_ring(upper_ring, upper_tip + one),
+            ring_neighbors[1],
+            position_in_ring(lower_ring, edge * lower_ring),
+            ring_neighbors[0],
+            position_in_ring(upper_ring, upper_tip + upper_ring * 6.into() - one),
+        ]
+    } else {
+        // 2 neighbors from the lower ring, the upper ring, and the same ring
+        let lower_neighbor1 = edge * lower_ring + tip_offset - one;
+        let upper_neighbor1 = edge * upper_ring + tip_offset;
+
+        [
+            position_in_ring(upper_ring, upper_neighbor1),
+            position_in_ring(upper_ring, upper_neighbor1 + one),
+            ring_neighbors[1],
+            position_in_ring(lower_ring, lower_neighbor1 + one),
+            position_in_ring(lower_ring, lower_neighbor1),
+            ring_neighbors[0],
+        ]
+    };
+
+    poss.rotate_right(edge_index);
+
+    poss
 }
 
 /// Returns `true` if the given 2 positions are neighbors.
-pub fn are_neighbors(pos1: Pos, pos2: Pos) -> bool {
+pub fn are_neighbors<P: PosInt>(pos1: P, pos2: P) -> bool {
     neighboring_positions(pos1).contains(&pos2)
 }
 
+/// Returns `true` if the given 2 positions are neighbors, or `None` if the neighbors of the
+/// first one can't be represented as a `P`.
+pub fn checked_are_neighbors<P: PosInt>(pos1: P, pos2: P) -> Option<bool> {
+    Some(checked_neighboring_positions(pos1)?.contains(&pos2))
+}
+
+/// Returns the distance between the given 2 positions, i.e. the number of steps between
+/// neighboring hexes it takes to get from one to the other.
+///
+/// # Panics
+///
+/// Panics if the ring of either position doesn't fit in an `i64`, unless the other one is `0`;
+/// this is only possible for `u128` positions.
+pub fn distance<P: PosInt>(pos1: P, pos2: P) -> P {
+    checked_distance(pos1, pos2).expect("ring index overflow")
+}
+
+/// Returns the distance between the given 2 positions, or `None` if the ring of either of them
+/// doesn't fit in an `i64`, unless the other one is `0`.
+pub fn checked_distance<P: PosInt>(pos1: P, pos2: P) -> Option<P> {
+    // The distance from the center is the ring index.
+    if pos1.is_zero() {
+        return Some(ring(pos2));
+    }
+    if pos2.is_zero() {
+        return Some(ring(pos1));
+    }
+
+    let distance = checked_spiral_to_cube(pos1)?.distance(&checked_spiral_to_cube(pos2)?);
+
+    // It can't exceed the sum of the rings of both positions, so it always fits in a `P`.
+    Some(num_traits::cast(distance).unwrap())
+}
+
 /// Returns `true` if the given list of positions consists of subsequent neighbors.
-pub fn is_path_consistent(poss: &[Pos]) -> bool {
+///
+/// # Panics
+///
+/// Panics if the list contains fewer than 2 positions, or positions whose neighbors can't be
+/// represented as a `P`; see [`checked_is_path_consistent`].
+pub fn is_path_consistent<P: PosInt>(poss: &[P]) -> bool {
     assert!(poss.len() >= 2);
 
     poss.windows(2).all(|pair| {
@@ -180,7 +380,54 @@ pub fn is_path_consistent(poss: &[Pos]) -> bool {
     })
 }
 
-pub fn are_grouped(poss: &[Pos]) -> bool {
+/// Returns `true` if the given list of positions consists of subsequent neighbors, or `None` if
+/// it contains fewer than 2 positions or ones whose neighbors can't be represented as a `P`.
+pub fn checked_is_path_consistent<P: PosInt>(poss: &[P]) -> Option<bool> {
+    if poss.len() < 2 {
+        return None;
+    }
+
+    for pair in poss.windows(2) {
+        if !checked_are_neighbors(pair[0], pair[1])? {
+            return Some(false);
+        }
+    }
+
+    Some(true)
+}
+
+/// Returns the positions of the hexes on the straight line between the given 2 positions,
+/// including both of them; every subsequent one is a neighbor of the previous one.
+///
+/// # Panics
+///
+/// Panics if any of the positions on the line can't be represented as a `P`, or the ring of
+/// either end doesn't fit in an `i64`, which is only possible for `u128` positions.
+#[cfg(feature = "alloc")]
+pub fn line<P: PosInt>(pos1: P, pos2: P) -> Vec<P> {
+    checked_line(pos1, pos2).expect("line position overflow")
+}
+
+/// Returns the positions of the hexes on the straight line between the given 2 positions,
+/// including both of them, or `None` if any of them can't be represented as a `P`, or the ring
+/// of either end doesn't fit in an `i64`.
+#[cfg(feature = "alloc")]
+pub fn checked_line<P: PosInt>(pos1: P, pos2: P) -> Option<Vec<P>> {
+    let (cube1, cube2) = (checked_spiral_to_cube(pos1)?, checked_spiral_to_cube(pos2)?);
+    let steps = cube1.distance(&cube2);
+
+    (0..=steps)
+        .map(|step| cube_to_spiral(cube1.lerp_round(&cube2, step, steps)).ok())
+        .collect()
+}
+
+#[cfg(feature = "std")]
+pub fn are_grouped<P: PosInt>(poss: &[P]) -> bool {
+    let poss = poss
+        .iter()
+        .map(|pos| pos.to_u128().unwrap())
+        .collect::<Vec<_>>();
+
     let mut rt = Crepe::new();
     rt.extend(poss.iter().copied().map(Position));
     let (groups,) = rt.run();
@@ -190,8 +437,136 @@ pub fn are_grouped(poss: &[Pos]) -> bool {
         .all(|pair| groups.contains(&Grouped(*pair[0], *pair[1])))
 }
 
+/// Returns `true` if the given positions form a single group of neighbors, or `None` if the
+/// neighbors of any of them can't be represented as a `P`.
+#[cfg(feature = "std")]
+pub fn checked_are_grouped<P: PosInt>(poss: &[P]) -> Option<bool> {
+    for pos in poss {
+        checked_neighboring_positions(*pos)?;
+    }
+
+    Some(are_grouped(poss))
+}
+
+/// A hex on the spiral grid, identified by its position.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hex<P = Pos>(P);
+
+impl<P: PosInt> Hex<P> {
+    /// Creates a new `Hex` at the given position.
+    pub const fn new(pos: P) -> Self {
+        Self(pos)
+    }
+
+    /// Returns the position of the hex.
+    pub fn pos(self) -> P {
+        self.0
+    }
+
+    /// The index of the ring the hex belongs to.
+    pub fn ring(self) -> P {
+        ring(self.0)
+    }
+
+    /// Returns the 6 neighbors of the hex, always in the same clockwise order.
+    pub fn neighbors(self) -> [Hex<P>; 6] {
+        neighboring_positions(self.0).map(Hex)
+    }
+
+    /// Returns the 6 neighbors of the hex, or `None` if any of them can't be represented.
+    pub fn checked_neighbors(self) -> Option<[Hex<P>; 6]> {
+        Some(checked_neighboring_positions(self.0)?.map(Hex))
+    }
+
+    /// Returns the neighbor of the hex in the given direction.
+    pub fn neighbor(self, dir: Direction) -> Hex<P> {
+        Hex(neighboring_positions(self.0)[dir.index()])
+    }
+
+    /// Returns the neighbor of the hex in the given direction, or `None` if it can't be
+    /// represented.
+    pub fn checked_neighbor(self, dir: Direction) -> Option<Hex<P>> {
+        partial_neighboring_positions(self.0)[dir.index()].map(Hex)
+    }
+
+    /// Returns `true` if the hex is at one of the tips of its ring.
+    pub fn is_tip(self) -> bool {
+        is_at_ring_tip(self.0)
+    }
+
+    /// Returns the index of the edge of the ring the hex belongs to.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the central hex, which doesn't belong to any edge; see
+    /// [`Hex::checked_edge_index`].
+    pub fn edge_index(self) -> usize {
+        ring_edge_index(self.0)
+    }
+
+    /// Returns the index of the edge of the ring the hex belongs to, or `None` for the central
+    /// hex.
+    pub fn checked_edge_index(self) -> Option<usize> {
+        checked_ring_edge_index(self.0)
+    }
+
+    /// Returns the distance to the other hex; see [`distance`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring of either hex doesn't fit in an `i64`, unless the other one is the
+    /// central hex.
+    pub fn distance(self, other: Self) -> P {
+        distance(self.0, other.0)
+    }
+
+    /// Returns the distance to the other hex, or `None` if the ring of either hex doesn't fit in
+    /// an `i64`, unless the other one is the central hex.
+    pub fn checked_distance(self, other: Self) -> Option<P> {
+        checked_distance(self.0, other.0)
+    }
+
+    /// Returns the cube coordinates of the hex.
+    pub fn to_cube(self) -> Cube {
+        spiral_to_cube(self.0)
+    }
+
+    /// Returns the center of the hex in window coordinates; see [`pos_to_point`].
+    #[cfg(any(feature = "std", feature = "libm"))]
+    pub fn to_point<F: Float + FloatConst>(
+        self,
+        r: F,
+        window_center: (F, F),
+        orientation: Orientation,
+    ) -> (F, F) {
+        pos_to_point(self.0, r, window_center, orientation)
+    }
+}
+
+impl<P: PosInt> From<P> for Hex<P> {
+    fn from(pos: P) -> Self {
+        Self(pos)
+    }
+}
+
+macro_rules! impl_from_hex {
+    ($($t:ty),*) => {
+        $(
+            impl From<Hex<$t>> for $t {
+                fn from(hex: Hex<$t>) -> Self {
+                    hex.0
+                }
+            }
+        )*
+    };
+}
+
+impl_from_hex!(u8, u16, u32, u64, u128, usize);
+
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "std")]
     use itertools::Itertools;
 
     use super::*;
@@ -218,28 +593,49 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn large_position_rings() {
+        for ring_idx in [
+            1_000usize,
+            65_535,
+            1 << 20,
+            3_000_000,
+            1 << 31,
+            2_479_700_524,
+        ] {
+            let offset = ring_offset(ring_idx);
+
+            assert_eq!(ring(offset - 1), ring_idx - 1);
+            assert_eq!(ring(offset), ring_idx);
+            assert_eq!(ring(offset + 6 * ring_idx - 1), ring_idx);
+        }
+
+        assert_eq!(ring(usize::MAX), 2_479_700_525);
+    }
+
     #[test]
     fn ring_tips() {
-        for pos in 1..=6 {
+        for pos in 1..=6usize {
             assert!(is_at_ring_tip(pos), "{}", pos);
         }
 
-        for pos in [7, 9, 11, 13, 15, 17] {
+        for pos in [7usize, 9, 11, 13, 15, 17] {
             assert!(is_at_ring_tip(pos), "{}", pos);
         }
 
-        for pos in [61, 66, 71, 76, 81, 86] {
+        for pos in [61usize, 66, 71, 76, 81, 86] {
             assert!(is_at_ring_tip(pos), "{}", pos);
         }
     }
 
     #[test]
     fn ring_edges() {
-        for pos in [8, 10, 12, 14, 16, 18] {
+        for pos in [8usize, 10, 12, 14, 16, 18] {
             assert!(!is_at_ring_tip(pos), "{}", pos);
         }
 
-        for pos in (62..66)
+        for pos in (62usize..66)
             .chain(67..71)
             .chain(72..76)
             .chain(77..81)
@@ -251,160 +647,476 @@ mod tests {
 
     #[test]
     fn edge_indices_non_tips() {
-        for pos in [8, 21].into_iter().chain(38..=40).chain(62..=65) {
+        for pos in [8usize, 21].into_iter().chain(38..=40).chain(62..=65) {
             assert_eq!(ring_edge_index(pos), 0);
         }
-        for pos in [10, 23, 24].into_iter().chain(42..=44).chain(67..=70) {
+        for pos in [10usize, 23, 24].into_iter().chain(42..=44).chain(67..=70) {
             assert_eq!(ring_edge_index(pos), 1);
         }
-        for pos in [12, 26, 27].into_iter().chain(46..=48).chain(72..=75) {
+        for pos in [12usize, 26, 27].into_iter().chain(46..=48).chain(72..=75) {
             assert_eq!(ring_edge_index(pos), 2);
         }
-        for pos in [14, 29, 30].into_iter().chain(50..=52).chain(77..=80) {
+        for pos in [14usize, 29, 30].into_iter().chain(50..=52).chain(77..=80) {
             assert_eq!(ring_edge_index(pos), 3);
         }
-        for pos in [16, 32, 33].into_iter().chain(54..=56).chain(82..=85) {
+        for pos in [16usize, 32, 33].into_iter().chain(54..=56).chain(82..=85) {
             assert_eq!(ring_edge_index(pos), 4);
         }
-        for pos in [18, 35, 36].into_iter().chain(58..=60).chain(87..=90) {
+        for pos in [18usize, 35, 36].into_iter().chain(58..=60).chain(87..=90) {
             assert_eq!(ring_edge_index(pos), 5);
         }
     }
 
     #[test]
     fn ring_neighbors() {
-        assert_eq!(ring_neighboring_positions(1), [6, 2]);
-        assert_eq!(ring_neighboring_positions(2), [1, 3]);
-        assert_eq!(ring_neighboring_positions(3), [2, 4]);
-        assert_eq!(ring_neighboring_positions(4), [3, 5]);
-        assert_eq!(ring_neighboring_positions(5), [4, 6]);
-        assert_eq!(ring_neighboring_positions(6), [5, 1]);
-        assert_eq!(ring_neighboring_positions(18), [17, 7]);
-        assert_eq!(ring_neighboring_positions(58), [57, 59]);
+        assert_eq!(ring_neighboring_positions(1usize), [Some(6), Some(2)]);
+        assert_eq!(ring_neighboring_positions(2usize), [Some(1), Some(3)]);
+        assert_eq!(ring_neighboring_positions(3usize), [Some(2), Some(4)]);
+        assert_eq!(ring_neighboring_positions(4usize), [Some(3), Some(5)]);
+        assert_eq!(ring_neighboring_positions(5usize), [Some(4), Some(6)]);
+        assert_eq!(ring_neighboring_positions(6usize), [Some(5), Some(1)]);
+        assert_eq!(ring_neighboring_positions(18usize), [Some(17), Some(7)]);
+        assert_eq!(ring_neighboring_positions(58usize), [Some(57), Some(59)]);
     }
 
     #[test]
     fn ring_tip_neighbors() {
-        assert_eq!(neighboring_positions(1), [7, 8, 2, 0, 6, 18]);
-        assert_eq!(neighboring_positions(2), [8, 9, 10, 3, 0, 1]);
-        assert_eq!(neighboring_positions(3), [2, 10, 11, 12, 4, 0]);
-        assert_eq!(neighboring_positions(4), [0, 3, 12, 13, 14, 5]);
-        assert_eq!(neighboring_positions(5), [6, 0, 4, 14, 15, 16]);
-        assert_eq!(neighboring_positions(6), [18, 1, 0, 5, 16, 17]);
-        assert_eq!(neighboring_positions(7), [19, 20, 8, 1, 18, 36]);
-        assert_eq!(neighboring_positions(9), [21, 22, 23, 10, 2, 8]);
-        assert_eq!(neighboring_positions(11), [10, 24, 25, 26, 12, 3]);
-        assert_eq!(neighboring_positions(13), [4, 12, 27, 28, 29, 14]);
-        assert_eq!(neighboring_positions(15), [16, 5, 14, 30, 31, 32]);
-        assert_eq!(neighboring_positions(17), [35, 18, 6, 16, 33, 34]);
-        assert_eq!(neighboring_positions(28), [13, 27, 48, 49, 50, 29]);
-        assert_eq!(neighboring_positions(53), [54, 31, 52, 80, 81, 82]);
-        assert_eq!(neighboring_positions(57), [87, 58, 34, 56, 85, 86]);
+        assert_eq!(neighboring_positions(1usize), [7, 8, 2, 0, 6, 18]);
+        assert_eq!(neighboring_positions(2usize), [8, 9, 10, 3, 0, 1]);
+        assert_eq!(neighboring_positions(3usize), [2, 10, 11, 12, 4, 0]);
+        assert_eq!(neighboring_positions(4usize), [0, 3, 12, 13, 14, 5]);
+        assert_eq!(neighboring_positions(5usize), [6, 0, 4, 14, 15, 16]);
+        assert_eq!(neighboring_positions(6usize), [18, 1, 0, 5, 16, 17]);
+        assert_eq!(neighboring_positions(7usize), [19, 20, 8, 1, 18, 36]);
+        assert_eq!(neighboring_positions(9usize), [21, 22, 23, 10, 2, 8]);
+        assert_eq!(neighboring_positions(11usize), [10, 24, 25, 26, 12, 3]);
+        assert_eq!(neighboring_positions(13usize), [4, 12, 27, 28, 29, 14]);
+        assert_eq!(neighboring_positions(15usize), [16, 5, 14, 30, 31, 32]);
+        assert_eq!(neighboring_positions(17usize), [35, 18, 6, 16, 33, 34]);
+        assert_eq!(neighboring_positions(28usize), [13, 27, 48, 49, 50, 29]);
+        assert_eq!(neighboring_positions(53usize), [54, 31, 52, 80, 81, 82]);
+        assert_eq!(neighboring_positions(57usize), [87, 58, 34, 56, 85, 86]);
     }
 
     #[test]
     fn ring_edge_neighbors() {
-        assert_eq!(neighboring_positions(8), [20, 21, 9, 2, 1, 7]);
-        assert_eq!(neighboring_positions(10), [9, 23, 24, 11, 3, 2]);
-        assert_eq!(neighboring_positions(12), [3, 11, 26, 27, 13, 4]);
-        assert_eq!(neighboring_positions(14), [5, 4, 13, 29, 30, 15]);
-        assert_eq!(neighboring_positions(16), [17, 6, 5, 15, 32, 33]);
-        assert_eq!(neighboring_positions(18), [36, 7, 1, 6, 17, 35]);
-        assert_eq!(neighboring_positions(38), [62, 63, 39, 20, 19, 37]);
-        assert_eq!(neighboring_positions(40), [64, 65, 41, 22, 21, 39]);
-        assert_eq!(neighboring_positions(42), [41, 67, 68, 43, 23, 22]);
-        assert_eq!(neighboring_positions(44), [43, 69, 70, 45, 25, 24]);
-        assert_eq!(neighboring_positions(46), [25, 45, 72, 73, 47, 26]);
-        assert_eq!(neighboring_positions(48), [27, 47, 74, 75, 49, 28]);
-        assert_eq!(neighboring_positions(50), [29, 28, 49, 77, 78, 51]);
-        assert_eq!(neighboring_positions(52), [31, 30, 51, 79, 80, 53]);
-        assert_eq!(neighboring_positions(54), [55, 32, 31, 53, 82, 83]);
-        assert_eq!(neighboring_positions(56), [57, 34, 33, 55, 84, 85]);
-        assert_eq!(neighboring_positions(58), [88, 59, 35, 34, 57, 87]);
-        assert_eq!(neighboring_positions(60), [90, 37, 19, 36, 59, 89]);
+        assert_eq!(neighboring_positions(8usize), [20, 21, 9, 2, 1, 7]);
+        assert_eq!(neighboring_positions(10usize), [9, 23, 24, 11, 3, 2]);
+        assert_eq!(neighboring_positions(12usize), [3, 11, 26, 27, 13, 4]);
+        assert_eq!(neighboring_positions(14usize), [5, 4, 13, 29, 30, 15]);
+        assert_eq!(neighboring_positions(16usize), [17, 6, 5, 15, 32, 33]);
+        assert_eq!(neighboring_positions(18usize), [36, 7, 1, 6, 17, 35]);
+        assert_eq!(neighboring_positions(38usize), [62, 63, 39, 20, 19, 37]);
+        assert_eq!(neighboring_positions(40usize), [64, 65, 41, 22, 21, 39]);
+        assert_eq!(neighboring_positions(42usize), [41, 67, 68, 43, 23, 22]);
+        assert_eq!(neighboring_positions(44usize), [43, 69, 70, 45, 25, 24]);
+        assert_eq!(neighboring_positions(46usize), [25, 45, 72, 73, 47, 26]);
+        assert_eq!(neighboring_positions(48usize), [27, 47, 74, 75, 49, 28]);
+        assert_eq!(neighboring_positions(50usize), [29, 28, 49, 77, 78, 51]);
+        assert_eq!(neighboring_positions(52usize), [31, 30, 51, 79, 80, 53]);
+        assert_eq!(neighboring_positions(54usize), [55, 32, 31, 53, 82, 83]);
+        assert_eq!(neighboring_positions(56usize), [57, 34, 33, 55, 84, 85]);
+        assert_eq!(neighboring_positions(58usize), [88, 59, 35, 34, 57, 87]);
+        assert_eq!(neighboring_positions(60usize), [90, 37, 19, 36, 59, 89]);
     }
 
     #[test]
+    #[cfg(feature = "std")]
     fn groups() {
-        assert!([2, 8, 9]
+        assert!([2usize, 8, 9]
             .into_iter()
             .permutations(3)
             .all(|perm| are_grouped(&perm)));
-        assert!([1, 0, 4]
+        assert!([1usize, 0, 4]
             .into_iter()
             .permutations(3)
             .all(|perm| are_grouped(&perm)));
-        assert!([71, 45, 25, 24, 23, 22, 41, 66]
+        assert!([71usize, 45, 25, 24, 23, 22, 41, 66]
             .into_iter()
             .permutations(8)
             .all(|perm| are_grouped(&perm)));
-        assert!([0, 1, 2, 3, 4, 5, 6]
+        assert!([0usize, 1, 2, 3, 4, 5, 6]
             .into_iter()
             .permutations(7)
             .all(|perm| are_grouped(&perm)));
-        assert!([5, 17, 18]
+        assert!([5usize, 17, 18]
             .into_iter()
             .permutations(3)
             .all(|perm| !are_grouped(&perm)));
-        assert!([2, 3, 5, 6]
+        assert!([2usize, 3, 5, 6]
             .into_iter()
             .permutations(4)
             .all(|perm| !are_grouped(&perm)));
-        assert!([1, 4]
+        assert!([1usize, 4]
             .into_iter()
             .permutations(2)
             .all(|perm| !are_grouped(&perm)));
 
-        assert!(are_grouped(&[11, 10, 2, 1, 6, 5, 15, 30, 29, 28, 27, 26]));
+        assert!(are_grouped(&[
+            11usize, 10, 2, 1, 6, 5, 15, 30, 29, 28, 27, 26
+        ]));
         assert!(!are_grouped(&[
-            1, 2, 3, 4, 5, 16, 17, 35, 36, 20, 21, 22, 23, 24, 25, 26
+            1usize, 2, 3, 4, 5, 16, 17, 35, 36, 20, 21, 22, 23, 24, 25, 26
         ]));
         assert!(are_grouped(&[
-            1, 2, 3, 4, 5, 16, 17, 35, 36, 19, 20, 21, 22, 23, 24, 25, 26
+            1usize, 2, 3, 4, 5, 16, 17, 35, 36, 19, 20, 21, 22, 23, 24, 25, 26
         ]));
+
+        assert_eq!(checked_are_grouped(&[1usize, 0, 4]), Some(true));
+        assert_eq!(checked_are_grouped(&[1usize, 4]), Some(false));
+        assert_eq!(checked_are_grouped(&[1usize, usize::MAX]), None);
     }
 
     #[test]
     fn directional_neighbor_iter() {
+        use Direction::*;
         use DirectionalNeighborIter as DNI;
 
         assert_eq!(
-            DNI::new(75, 0).take(9).collect::<Vec<_>>(),
+            DNI::new(75usize, N).take(9).collect::<Vec<_>>(),
             vec![48, 27, 12, 3, 2, 8, 20, 38, 62]
         );
         assert_eq!(
-            DNI::new(76, 0).take(10).collect::<Vec<_>>(),
+            DNI::new(76usize, N).take(10).collect::<Vec<_>>(),
             vec![49, 28, 13, 4, 0, 1, 7, 19, 37, 61]
         );
         assert_eq!(
-            DNI::new(77, 0).take(9).collect::<Vec<_>>(),
+            DNI::new(77usize, N).take(9).collect::<Vec<_>>(),
             vec![50, 29, 14, 5, 6, 18, 36, 60, 90]
         );
 
         assert_eq!(
-            DNI::new(80, 1).take(9).collect::<Vec<_>>(),
+            DNI::new(80usize, NE).take(9).collect::<Vec<_>>(),
             vec![52, 30, 14, 4, 3, 10, 23, 42, 67]
         );
         assert_eq!(
-            DNI::new(81, 1).take(10).collect::<Vec<_>>(),
+            DNI::new(81usize, NE).take(10).collect::<Vec<_>>(),
             vec![53, 31, 15, 5, 0, 2, 9, 22, 41, 66]
         );
         assert_eq!(
-            DNI::new(82, 1).take(9).collect::<Vec<_>>(),
+            DNI::new(82usize, NE).take(9).collect::<Vec<_>>(),
             vec![54, 32, 16, 6, 1, 8, 21, 40, 65]
         );
 
         assert_eq!(
-            DNI::new(85, 2).take(9).collect::<Vec<_>>(),
+            DNI::new(85usize, SE).take(9).collect::<Vec<_>>(),
             vec![56, 33, 16, 5, 4, 12, 26, 46, 72]
         );
         assert_eq!(
-            DNI::new(86, 2).take(10).collect::<Vec<_>>(),
+            DNI::new(86usize, SE).take(10).collect::<Vec<_>>(),
             vec![57, 34, 17, 6, 0, 3, 11, 25, 45, 71]
         );
         assert_eq!(
-            DNI::new(87, 2).take(9).collect::<Vec<_>>(),
+            DNI::new(87usize, SE).take(9).collect::<Vec<_>>(),
             vec![58, 35, 18, 1, 2, 10, 24, 44, 70]
         );
     }
+
+    #[test]
+    fn hexes() {
+        for pos in 0..ring_offset(6usize) {
+            let hex = Hex::from(pos);
+
+            assert_eq!(Pos::from(hex), pos);
+            assert_eq!(hex.ring(), ring(pos));
+            assert_eq!(hex.is_tip(), is_at_ring_tip(pos));
+            assert_eq!(hex.to_cube(), spiral_to_cube(pos));
+            #[cfg(any(feature = "std", feature = "libm"))]
+            assert_eq!(
+                hex.to_point(10.0, (5.0, 5.0), Orientation::Pointy),
+                pos_to_point(pos, 10.0, (5.0, 5.0), Orientation::Pointy)
+            );
+            assert_eq!(hex.neighbors().map(Hex::pos), neighboring_positions(pos));
+            for dir in Direction::iter() {
+                assert_eq!(hex.neighbor(dir), hex.neighbors()[dir.index()]);
+            }
+            assert_eq!(hex.checked_edge_index(), checked_ring_edge_index(pos));
+            if pos != 0 {
+                assert_eq!(hex.edge_index(), ring_edge_index(pos));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn central_hex_edge_index() {
+        Hex::new(0usize).edge_index();
+    }
+
+    #[test]
+    fn directions() {
+        use Direction::*;
+
+        assert_eq!(Direction::iter().collect::<Vec<_>>(), Direction::ALL);
+        assert_eq!(N.rotate_cw(), NE);
+        assert_eq!(NW.rotate_cw(), N);
+        assert_eq!(N.rotate_ccw(), NW);
+        assert_eq!(SE.rotate_ccw(), NE);
+
+        for dir in Direction::iter() {
+            assert_eq!(dir.rotate_cw().rotate_ccw(), dir);
+            assert_eq!(dir.opposite().opposite(), dir);
+            assert_eq!(dir.opposite(), dir.rotate_cw().rotate_cw().rotate_cw());
+            assert_eq!(Direction::try_from(usize::from(dir)), Ok(dir));
+
+            // going in one direction and then the opposite one leads back to the start
+            for pos in 0..ring_offset(5usize) {
+                let neighbor = neighboring_positions(pos)[dir.index()];
+                assert_eq!(neighboring_positions(neighbor)[dir.opposite().index()], pos);
+            }
+        }
+
+        assert_eq!(Direction::try_from(6), Err(HexError::InvalidDirection(6)));
+    }
+
+    #[test]
+    fn checked_functions() {
+        for ring_idx in 0..20usize {
+            assert_eq!(checked_ring_offset(ring_idx), Some(ring_offset(ring_idx)));
+        }
+        assert_eq!(checked_ring_offset(usize::MAX), None);
+
+        assert_eq!(checked_ring_edge_index(0usize), None);
+        assert_eq!(Hex::new(0usize).checked_edge_index(), None);
+        for pos in 1..ring_offset(6usize) {
+            assert_eq!(checked_ring_edge_index(pos), Some(ring_edge_index(pos)));
+            assert_eq!(
+                checked_neighboring_positions(pos),
+                Some(neighboring_positions(pos))
+            );
+        }
+
+        assert_eq!(checked_neighboring_positions(usize::MAX), None);
+        assert_eq!(checked_are_neighbors(usize::MAX, usize::MAX - 1), None);
+        assert_eq!(Hex::new(usize::MAX).checked_neighbors(), None);
+        assert_eq!(Hex::new(usize::MAX).checked_neighbor(Direction::N), None);
+        assert_eq!(
+            Hex::new(usize::MAX).checked_neighbor(Direction::NW),
+            Some(Hex::new(usize::MAX - 1))
+        );
+        assert_eq!(checked_are_neighbors(8usize, 20), Some(true));
+        assert_eq!(checked_are_neighbors(8usize, 22), Some(false));
+
+        assert_eq!(checked_is_path_consistent::<Pos>(&[]), None);
+        assert_eq!(checked_is_path_consistent(&[5usize]), None);
+        assert_eq!(checked_is_path_consistent(&[5usize, 0, 2]), Some(true));
+        assert_eq!(checked_is_path_consistent(&[5usize, 2]), Some(false));
+        assert_eq!(
+            checked_is_path_consistent(&[5usize, 2, usize::MAX]),
+            Some(false)
+        );
+        assert_eq!(
+            checked_is_path_consistent(&[5usize, usize::MAX]),
+            Some(false)
+        );
+        assert_eq!(
+            checked_is_path_consistent(&[0usize, 1, usize::MAX]),
+            Some(false)
+        );
+        assert_eq!(checked_is_path_consistent(&[usize::MAX, 0]), None);
+    }
+
+    #[test]
+    fn directional_neighbor_iter_overflow() {
+        // The iterator ends instead of panicking once it runs out of representable positions
+        let last_ring = ring(usize::MAX);
+        let start = ring_offset(last_ring - 2);
+        let poss = DirectionalNeighborIter::new(start, Direction::N).collect::<Vec<_>>();
+
+        assert_eq!(poss, [ring_offset(last_ring - 1), ring_offset(last_ring)]);
+    }
+
+    #[test]
+    fn position_widths() {
+        for pos in 0..ring_offset(10usize) {
+            let neighbors = neighboring_positions(pos);
+
+            assert_eq!(ring(pos as u16) as usize, ring(pos));
+            assert_eq!(
+                neighboring_positions(pos as u16).map(usize::from),
+                neighbors
+            );
+            assert_eq!(
+                neighboring_positions(pos as u32).map(|p| p as usize),
+                neighbors
+            );
+            assert_eq!(
+                neighboring_positions(pos as u64).map(|p| p as usize),
+                neighbors
+            );
+            assert_eq!(
+                neighboring_positions(pos as u128).map(|p| p as usize),
+                neighbors
+            );
+            assert_eq!(
+                Hex::new(pos as u32).neighbors().map(|h| h.pos() as usize),
+                neighbors
+            );
+        }
+
+        // The largest rings are handled without overflow for every type
+        assert_eq!(ring(u8::MAX), 9);
+        assert_eq!(ring(u16::MAX), 148);
+        assert_eq!(ring(u32::MAX), 37_837);
+        assert_eq!(ring(u128::MAX), 10_650_232_656_628_343_401);
+
+        assert_eq!(checked_ring_offset(10u8), None);
+        assert_eq!(checked_neighboring_positions(ring_offset(9u8)), None);
+        assert_eq!(
+            checked_neighboring_positions(ring_offset(7u8)).map(|n| n.map(usize::from)),
+            Some(neighboring_positions(ring_offset(7usize)))
+        );
+        assert_eq!(
+            DirectionalNeighborIter::new(0u8, Direction::S).last(),
+            Some(ring_offset(9) + 3 * 9)
+        );
+
+        // Only the neighbors that don't fit in the type are missing
+        for pos in 0..=u8::MAX {
+            let neighbors = neighboring_positions(pos as usize).map(|p| u8::try_from(p).ok());
+
+            assert_eq!(
+                checked_neighboring_positions(pos).map(|n| n.map(Some)),
+                neighbors.iter().all(Option::is_some).then_some(neighbors)
+            );
+            for dir in Direction::iter() {
+                assert_eq!(
+                    Hex::new(pos).checked_neighbor(dir).map(Hex::pos),
+                    neighbors[dir.index()]
+                );
+            }
+        }
+
+        let last_ring = ring(usize::MAX);
+        assert_eq!(
+            Hex::new(ring_offset(last_ring - 1)).checked_neighbor(Direction::N),
+            Some(Hex::new(ring_offset(last_ring)))
+        );
+        assert_eq!(
+            Hex::new(ring_offset(last_ring - 1)).checked_neighbors(),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "neighboring position overflow")]
+    fn position_overflow() {
+        neighboring_positions(u16::MAX);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let json = serde_json::to_string(&Direction::ALL).unwrap();
+        assert_eq!(json, r#"["N","NE","SE","S","SW","NW"]"#);
+        assert_eq!(
+            serde_json::from_str::<[Direction; 6]>(&json).unwrap(),
+            Direction::ALL
+        );
+
+        let hexes = [Hex::new(0u16), Hex::new(8), Hex::new(u16::MAX)];
+        let json = serde_json::to_string(&hexes).unwrap();
+        assert_eq!(json, "[0,8,65535]");
+        assert_eq!(serde_json::from_str::<[Hex<u16>; 3]>(&json).unwrap(), hexes);
+        assert!(serde_json::from_str::<Hex<u8>>("256").is_err());
+    }
+
+    #[test]
+    fn distances() {
+        use std::collections::{hash_map::Entry, HashMap, VecDeque};
+
+        for pos in 0..ring_offset(8usize) {
+            assert_eq!(distance(0, pos), ring(pos));
+            assert_eq!(distance(pos, 0), ring(pos));
+            assert_eq!(distance(pos, pos), 0);
+
+            for neighbor in neighboring_positions(pos) {
+                assert_eq!(distance(pos, neighbor), 1);
+            }
+        }
+
+        // compare with the number of steps found with a breadth-first search
+        for start in [1usize, 8, 15, 40, 60] {
+            let mut steps = HashMap::from([(start, 0)]);
+            let mut queue = VecDeque::from([start]);
+
+            while let Some(pos) = queue.pop_front() {
+                let dist = steps[&pos];
+                assert_eq!(distance(start, pos), dist);
+                assert_eq!(Hex::new(pos).distance(Hex::new(start)), dist);
+
+                if dist < 6 {
+                    for neighbor in neighboring_positions(pos) {
+                        if let Entry::Vacant(entry) = steps.entry(neighbor) {
+                            entry.insert(dist + 1);
+                            queue.push_back(neighbor);
+                        }
+                    }
+                }
+            }
+        }
+
+        // the opposite tips of a ring are twice the ring index apart
+        for ring in [10u128, 1 << 20, 1 << 40, i64::MAX as u128] {
+            let n_tip = ring_offset(ring);
+            assert_eq!(distance(n_tip, n_tip + 3 * ring), 2 * ring);
+            assert_eq!(distance(n_tip + ring, n_tip + 4 * ring), 2 * ring);
+        }
+        assert_eq!(distance(ring_offset(9u8), u8::MAX), 16);
+
+        assert_eq!(checked_distance(0, u128::MAX), Some(ring(u128::MAX)));
+        assert_eq!(checked_distance(u128::MAX, 1), None);
+        assert_eq!(Hex::new(1).checked_distance(Hex::new(u128::MAX)), None);
+        assert_eq!(Hex::new(1usize).checked_distance(Hex::new(8)), Some(1));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn lines() {
+        assert_eq!(line(5usize, 5), [5]);
+        assert_eq!(line(0usize, 7), [0, 1, 7]);
+        assert_eq!(line(0usize, 37), [0, 1, 7, 19, 37]);
+        assert_eq!(line(0usize, 28), [0, 4, 13, 28]);
+
+        // the ties are always broken in the same way, regardless of the direction
+        assert_eq!(line(0usize, 8), [0, 2, 8]);
+        assert_eq!(line(8usize, 0), [8, 2, 0]);
+
+        for pos1 in 0..ring_offset(5usize) {
+            for pos2 in 0..ring_offset(5usize) {
+                let line = line(pos1, pos2);
+
+                assert!(line.len() == 1 || is_path_consistent(&line));
+                assert_eq!(line.len(), distance(pos1, pos2) + 1);
+                assert_eq!((line[0], line[line.len() - 1]), (pos1, pos2));
+            }
+        }
+
+        // the edges of the rings are straight lines
+        for ring in [10u64, 1 << 20, 1 << 30, 2_000_000_000] {
+            let start = ring_offset(ring) + ring;
+            assert_eq!(
+                line(start, start + 7),
+                (start..=start + 7).collect::<Vec<_>>()
+            );
+        }
+
+        // lines between representable positions can cross the unrepresentable ones
+        let mut overflows = 0;
+        for pos1 in ring_offset(9u8)..=u8::MAX {
+            for pos2 in ring_offset(9u8)..=u8::MAX {
+                let expected = line(pos1 as usize, pos2 as usize)
+                    .into_iter()
+                    .map(|pos| u8::try_from(pos).ok())
+                    .collect::<Option<Vec<_>>>();
+                overflows += expected.is_none() as usize;
+
+                assert_eq!(checked_line(pos1, pos2), expected);
+            }
+        }
+        assert!(overflows > 0);
+
+        assert_eq!(checked_line(u128::MAX, 0), None);
+        assert_eq!(checked_line(0, u128::MAX), None);
+    }
 }